@@ -0,0 +1,32 @@
+//! Helpers shared by the benchmarks.
+
+use std::time::{Duration, Instant};
+
+/// Returns `len` reproducible pseudo random bytes.
+pub fn input(len: usize) -> Vec<u8> {
+	let mut seed = 0x9e3779b9u32;
+	(0..len).map(|_| {
+		seed ^= seed << 13;
+		seed ^= seed >> 17;
+		seed ^= seed << 5;
+		seed as u8
+	}).collect()
+}
+
+/// Returns the mean time of a call of `f`, repeating it for at least 200ms.
+///
+/// Calls are timed in batches of up to 100, so fast functions are not dominated by reading the clock and slow
+/// ones are not repeated much past the 200ms.
+pub fn measure<F: FnMut()>(mut f: F) -> Duration {
+	let start = Instant::now();
+	let mut iterations = 0u32;
+	let mut batch = 1;
+	while start.elapsed() < Duration::from_millis(200) {
+		for _ in 0..batch {
+			f();
+		}
+		iterations += batch;
+		batch = (batch * 2).min(100);
+	}
+	start.elapsed() / iterations
+}
@@ -0,0 +1,91 @@
+//! Compares `decode_into` and `decode_array` with the one character at a time decoder it replaced.
+//!
+//! Run with `cargo bench --bench decode`.
+
+extern crate base58;
+
+mod common;
+
+use std::hint::black_box;
+use std::time::Duration;
+use base58::{decode_array, decode_into, encode_into, max_encoded_len};
+use common::{input, measure};
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The previous decoder, multiplying the whole number by 58 for every character.
+fn bytewise_decode_into(input: &str, output: &mut [u8], digits: &[u8; 128]) -> usize {
+	let b58 = input.as_bytes();
+	let zcount = b58.iter().take_while(|x| **x == ALPHABET[0]).count();
+	let (zeros, number) = output.split_at_mut(zcount);
+	let size = number.len();
+	let mut used = 0;
+
+	for c in &b58[zcount..] {
+		let mut carry = digits[*c as usize] as u32;
+		for byte in number[size - used..].iter_mut().rev() {
+			carry += *byte as u32 * 58;
+			*byte = carry as u8;
+			carry >>= 8;
+		}
+		while carry != 0 {
+			used += 1;
+			number[size - used] = carry as u8;
+			carry >>= 8;
+		}
+	}
+
+	number.copy_within(size - used.., 0);
+	for zero in zeros.iter_mut() {
+		*zero = 0;
+	}
+	zcount + used
+}
+
+fn encoded(len: usize) -> String {
+	let input = input(len);
+	let mut output = vec![0u8; max_encoded_len(len)];
+	let written = encode_into(&input, &mut output).unwrap();
+	String::from_utf8(output[..written].to_vec()).unwrap()
+}
+
+fn run<const N: usize>(digits: &[u8; 128]) {
+	let input = encoded(N);
+	let mut expected = vec![0u8; N];
+	let mut output = vec![0u8; N];
+	let expected_len = bytewise_decode_into(&input, &mut expected, digits);
+	let len_written = decode_into(&input, &mut output).unwrap();
+	assert_eq!(output[..len_written], expected[..expected_len], "output differs for {} bytes", N);
+	assert_eq!(decode_array::<N>(&input).unwrap()[..], expected[..expected_len]);
+
+	let bytewise = measure(|| {
+		black_box(bytewise_decode_into(black_box(&input), &mut expected, digits));
+	});
+	let limbs = measure(|| {
+		black_box(decode_into(black_box(&input), &mut output).unwrap());
+	});
+	let array = measure(|| {
+		black_box(decode_array::<N>(black_box(&input)).unwrap());
+	});
+	let speedup = |x: Duration| bytewise.as_secs_f64() / x.as_secs_f64();
+	println!(
+		"{:>6} {:>12?} {:>12?} {:>7.1}x {:>12?} {:>7.1}x",
+		N, bytewise, limbs, speedup(limbs), array, speedup(array)
+	);
+}
+
+fn main() {
+	let mut digits = [0u8; 128];
+	for (i, c) in ALPHABET.iter().enumerate() {
+		digits[*c as usize] = i as u8;
+	}
+
+	println!("{:>6} {:>12} {:>12} {:>8} {:>12} {:>8}", "bytes", "bytewise", "limbs", "speedup", "array", "speedup");
+	run::<20>(&digits);
+	run::<25>(&digits);
+	run::<32>(&digits);
+	run::<33>(&digits);
+	run::<64>(&digits);
+	run::<256>(&digits);
+	run::<1024>(&digits);
+}
@@ -0,0 +1,68 @@
+//! Compares `encode_into` with the byte-at-a-time encoder it replaced.
+//!
+//! Run with `cargo bench --bench encode`.
+
+extern crate base58;
+
+mod common;
+
+use std::hint::black_box;
+use base58::{encode_into, max_encoded_len};
+use common::{input, measure};
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The previous encoder, one input byte and one base58 digit per step.
+fn bytewise_encode_into(input: &[u8], output: &mut [u8]) -> usize {
+	let zcount = input.iter().take_while(|x| **x == 0).count();
+	let size = max_encoded_len(input.len() - zcount);
+	let required = zcount + size;
+	let buffer = &mut output[zcount..required];
+	for digit in buffer.iter_mut() {
+		*digit = 0;
+	}
+
+	let mut high = size - 1;
+	for x in &input[zcount..] {
+		let mut carry = *x as u32;
+		let mut j = size - 1;
+		while j > high || carry != 0 {
+			carry += 256 * buffer[j] as u32;
+			buffer[j] = (carry % 58) as u8;
+			carry /= 58;
+			j = j.saturating_sub(1);
+		}
+		high = j;
+	}
+
+	let skip = buffer.iter().take_while(|x| **x == 0).count();
+	let len = zcount + size - skip;
+	output.copy_within(zcount + skip..required, zcount);
+	for c in output[..zcount].iter_mut() {
+		*c = ALPHABET[0];
+	}
+	for c in output[zcount..len].iter_mut() {
+		*c = ALPHABET[*c as usize];
+	}
+	len
+}
+
+fn main() {
+	println!("{:>6} {:>12} {:>12} {:>8}", "bytes", "bytewise", "limbs", "speedup");
+	for len in &[20, 25, 32, 33, 64, 256, 1024] {
+		let input = input(*len);
+		let mut expected = vec![0u8; max_encoded_len(*len)];
+		let mut output = vec![0u8; max_encoded_len(*len)];
+		let expected_len = bytewise_encode_into(&input, &mut expected);
+		let len_written = encode_into(&input, &mut output).unwrap();
+		assert_eq!(output[..len_written], expected[..expected_len], "output differs for {} bytes", len);
+
+		let bytewise = measure(|| {
+			black_box(bytewise_encode_into(black_box(&input), &mut expected));
+		});
+		let limbs = measure(|| {
+			black_box(encode_into(black_box(&input), &mut output).unwrap());
+		});
+		println!("{:>6} {:>12?} {:>12?} {:>7.1}x", len, bytewise, limbs, bytewise.as_secs_f64() / limbs.as_secs_f64());
+	}
+}
@@ -0,0 +1,45 @@
+//! Compares the quadratic `encode_into` and `decode_into` with the divide and conquer conversion
+//! `to_base58` and `from_base58` switch to for large inputs.
+//!
+//! Run with `cargo bench --bench large`.
+
+extern crate base58;
+
+mod common;
+
+use std::hint::black_box;
+use base58::{decode_into, encode_into, max_encoded_len, FromBase58, ToBase58};
+use common::{input, measure};
+
+fn main() {
+	println!("{:>8} {:>12} {:>12} {:>12} {:>12}", "bytes", "encode_into", "to_base58", "decode_into", "from_base58");
+	for len in &[1024, 2048, 4096, 8192, 16384, 65536, 1 << 20] {
+		let input = input(*len);
+		let encoded = input.to_base58();
+		assert_eq!(encoded.from_base58().unwrap(), input);
+
+		let mut buffer = vec![0u8; max_encoded_len(*len)];
+		// the quadratic conversion of a megabyte takes minutes
+		let (encode, decode) = if *len <= 65536 {
+			let written = encode_into(&input, &mut buffer).unwrap();
+			assert_eq!(&buffer[..written], encoded.as_bytes());
+			let encode = measure(|| {
+				black_box(encode_into(black_box(&input), &mut buffer).unwrap());
+			});
+			let decode = measure(|| {
+				black_box(decode_into(black_box(&encoded), &mut buffer).unwrap());
+			});
+			(format!("{:?}", encode), format!("{:?}", decode))
+		} else {
+			("-".into(), "-".into())
+		};
+
+		let to_base58 = measure(|| {
+			black_box(black_box(&input[..]).to_base58());
+		});
+		let from_base58 = measure(|| {
+			black_box(black_box(&encoded[..]).from_base58().unwrap());
+		});
+		println!("{:>8} {:>12} {:>12?} {:>12} {:>12?}", len, encode, to_base58, decode, from_base58);
+	}
+}
@@ -0,0 +1,328 @@
+//! Legacy bitcoin addresses, a version byte and a 20-byte hash in base58check
+
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "hash160")]
+use alloc::string::String;
+use alloc::vec::Vec;
+use check::encode_versioned;
+#[cfg(feature = "hash160")]
+use ripemd160::hash160;
+use {FromBase58Check, FromBase58Error};
+
+/// Length of the hash in an address.
+const HASH_LEN: usize = 20;
+
+const OP_DUP: u8 = 0x76;
+const OP_HASH160: u8 = 0xa9;
+const OP_EQUAL: u8 = 0x87;
+const OP_EQUALVERIFY: u8 = 0x88;
+const OP_CHECKSIG: u8 = 0xac;
+/// Pushes the next 20 bytes of the script.
+const OP_PUSH_20: u8 = 0x14;
+
+/// Bitcoin network an address belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitcoinNetwork {
+	/// The main network.
+	Mainnet,
+	/// The test networks, testnet, signet and regtest share their version bytes.
+	Testnet,
+}
+
+/// Kind of a legacy bitcoin address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitcoinAddressKind {
+	/// Pay to public key hash, the hash is the HASH160 of a public key.
+	P2pkh,
+	/// Pay to script hash, the hash is the HASH160 of a redeem script.
+	P2sh,
+}
+
+/// A legacy bitcoin address, e.g. `1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitcoinAddress {
+	/// Network of the address.
+	pub network: BitcoinNetwork,
+	/// Kind of the address.
+	pub kind: BitcoinAddressKind,
+	/// HASH160 of the public key or the redeem script.
+	pub hash: [u8; HASH_LEN],
+}
+
+impl BitcoinAddress {
+	/// Creates an address of `kind` for `network` from the hash.
+	pub fn new(network: BitcoinNetwork, kind: BitcoinAddressKind, hash: [u8; HASH_LEN]) -> Self {
+		BitcoinAddress { network, kind, hash }
+	}
+
+	/// Creates a P2PKH address for `network` from a serialized secp256k1 public key.
+	///
+	/// Returns `None` unless the key is 33 bytes starting with 0x02 or 0x03, or 65 bytes starting with 0x04.
+	#[cfg(feature = "hash160")]
+	pub fn from_public_key(network: BitcoinNetwork, public_key: &[u8]) -> Option<Self> {
+		public_key_hash(public_key).map(|hash| BitcoinAddress::new(network, BitcoinAddressKind::P2pkh, hash))
+	}
+
+	/// Creates a P2SH address for `network` from a redeem script.
+	#[cfg(feature = "hash160")]
+	pub fn from_script(network: BitcoinNetwork, script: &[u8]) -> Self {
+		BitcoinAddress::new(network, BitcoinAddressKind::P2sh, hash160(script))
+	}
+
+	/// Returns the version byte of the address.
+	pub fn version(&self) -> u8 {
+		match (self.network, self.kind) {
+			(BitcoinNetwork::Mainnet, BitcoinAddressKind::P2pkh) => 0x00,
+			(BitcoinNetwork::Mainnet, BitcoinAddressKind::P2sh) => 0x05,
+			(BitcoinNetwork::Testnet, BitcoinAddressKind::P2pkh) => 0x6f,
+			(BitcoinNetwork::Testnet, BitcoinAddressKind::P2sh) => 0xc4,
+		}
+	}
+
+	/// Parses a legacy address, verifying its checksum.
+	///
+	/// Payloads of other than 21 bytes fail with [`FromBase58Error::UnexpectedLength`] and unknown version
+	/// bytes with [`FromBase58Error::InvalidVersion`].
+	pub fn from_base58check(input: &str) -> Result<Self, FromBase58Error> {
+		let data = input.from_base58check()?;
+		if data.len() != 1 + HASH_LEN {
+			return Err(FromBase58Error::UnexpectedLength { expected: 1 + HASH_LEN, actual: data.len() });
+		}
+
+		let (network, kind) = match data[0] {
+			0x00 => (BitcoinNetwork::Mainnet, BitcoinAddressKind::P2pkh),
+			0x05 => (BitcoinNetwork::Mainnet, BitcoinAddressKind::P2sh),
+			0x6f => (BitcoinNetwork::Testnet, BitcoinAddressKind::P2pkh),
+			0xc4 => (BitcoinNetwork::Testnet, BitcoinAddressKind::P2sh),
+			_ => return Err(FromBase58Error::invalid_version(&data)),
+		};
+
+		let mut hash = [0u8; HASH_LEN];
+		hash.copy_from_slice(&data[1..]);
+		Ok(BitcoinAddress { network, kind, hash })
+	}
+
+	/// Returns the output script paying to the address.
+	///
+	/// `OP_DUP OP_HASH160 <hash> OP_EQUALVERIFY OP_CHECKSIG` for P2PKH and `OP_HASH160 <hash> OP_EQUAL` for P2SH.
+	pub fn script_pubkey(&self) -> Vec<u8> {
+		let mut script = Vec::with_capacity(HASH_LEN + 5);
+		match self.kind {
+			BitcoinAddressKind::P2pkh => {
+				script.extend_from_slice(&[OP_DUP, OP_HASH160, OP_PUSH_20]);
+				script.extend_from_slice(&self.hash);
+				script.extend_from_slice(&[OP_EQUALVERIFY, OP_CHECKSIG]);
+			},
+			BitcoinAddressKind::P2sh => {
+				script.extend_from_slice(&[OP_HASH160, OP_PUSH_20]);
+				script.extend_from_slice(&self.hash);
+				script.push(OP_EQUAL);
+			},
+		}
+		script
+	}
+
+	/// Recognizes a P2PKH or P2SH output script and returns its address on `network`.
+	///
+	/// Returns `None` for any other script, including segwit outputs, which have no base58 address.
+	pub fn from_script_pubkey(network: BitcoinNetwork, script: &[u8]) -> Option<Self> {
+		let (kind, hash) = match *script {
+			[OP_DUP, OP_HASH160, OP_PUSH_20, ref hash @ .., OP_EQUALVERIFY, OP_CHECKSIG] =>
+				(BitcoinAddressKind::P2pkh, hash),
+			[OP_HASH160, OP_PUSH_20, ref hash @ .., OP_EQUAL] => (BitcoinAddressKind::P2sh, hash),
+			_ => return None,
+		};
+		if hash.len() != HASH_LEN {
+			return None;
+		}
+
+		let mut address = BitcoinAddress::new(network, kind, [0u8; HASH_LEN]);
+		address.hash.copy_from_slice(hash);
+		Some(address)
+	}
+}
+
+/// Returns the HASH160 of a serialized secp256k1 public key, `None` unless the key is 33 bytes starting with
+/// 0x02 or 0x03, or 65 bytes starting with 0x04.
+#[cfg(feature = "hash160")]
+fn public_key_hash(public_key: &[u8]) -> Option<[u8; HASH_LEN]> {
+	match (public_key.len(), public_key.first()) {
+		(33, Some(0x02)) | (33, Some(0x03)) | (65, Some(0x04)) => Some(hash160(public_key)),
+		_ => None,
+	}
+}
+
+/// Returns the pay to public key hash address with `version` of a serialized secp256k1 public key, for coins
+/// other than bitcoin, e.g. 0x30 for litecoin.
+///
+/// Returns `None` for the same keys as [`BitcoinAddress::from_public_key`].
+#[cfg(feature = "hash160")]
+pub fn public_key_address(version: u8, public_key: &[u8]) -> Option<String> {
+	public_key_hash(public_key).map(|hash| encode_versioned(&[version], &hash))
+}
+
+/// Returns the pay to script hash address with `version` of a redeem script, e.g. 0x32 for litecoin.
+#[cfg(feature = "hash160")]
+pub fn script_address(version: u8, script: &[u8]) -> String {
+	encode_versioned(&[version], &hash160(script))
+}
+
+impl fmt::Display for BitcoinAddress {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&encode_versioned(&[self.version()], &self.hash))
+	}
+}
+
+impl FromStr for BitcoinAddress {
+	type Err = FromBase58Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		BitcoinAddress::from_base58check(s)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString;
+	use super::{BitcoinAddress, BitcoinAddressKind, BitcoinNetwork};
+	use {FromBase58Error, ToBase58Check};
+
+	const GENESIS_HASH: [u8; 20] = [
+		0x62, 0xe9, 0x07, 0xb1, 0x5c, 0xbf, 0x27, 0xd5, 0x42, 0x53, 0x99, 0xeb, 0xf6, 0xf0, 0xfb, 0x50,
+		0xeb, 0xb8, 0x8f, 0x18,
+	];
+
+	#[test]
+	fn test_parse_address() {
+		let address: BitcoinAddress = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap();
+		assert_eq!(address, BitcoinAddress::new(BitcoinNetwork::Mainnet, BitcoinAddressKind::P2pkh, GENESIS_HASH));
+		assert_eq!(address.version(), 0);
+	}
+
+	#[test]
+	fn test_address_networks_and_kinds() {
+		let addresses = [
+			("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", BitcoinNetwork::Mainnet, BitcoinAddressKind::P2pkh, 0x00),
+			("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy", BitcoinNetwork::Mainnet, BitcoinAddressKind::P2sh, 0x05),
+			("mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn", BitcoinNetwork::Testnet, BitcoinAddressKind::P2pkh, 0x6f),
+			("2MzQwSSnBHWHqSAqtTVQ6v47XtaisrJa1Vc", BitcoinNetwork::Testnet, BitcoinAddressKind::P2sh, 0xc4),
+		];
+
+		for (input, network, kind, version) in addresses {
+			let address = BitcoinAddress::from_base58check(input).unwrap();
+			assert_eq!((address.network, address.kind, address.version()), (network, kind, version));
+			assert_eq!(address.to_string(), input);
+		}
+	}
+
+	#[test]
+	fn test_parse_address_invalid() {
+		assert!(matches!("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb".parse::<BitcoinAddress>(),
+			Err(FromBase58Error::InvalidChecksum { .. })));
+
+		let mut data = [0u8; 22];
+		assert_eq!(BitcoinAddress::from_base58check(&data.to_base58check()),
+			Err(FromBase58Error::UnexpectedLength { expected: 21, actual: 22 }));
+		assert_eq!(BitcoinAddress::from_base58check(&data[..20].to_base58check()),
+			Err(FromBase58Error::UnexpectedLength { expected: 21, actual: 20 }));
+
+		// litecoin P2PKH
+		data[0] = 0x30;
+		assert_eq!(BitcoinAddress::from_base58check(&data[..21].to_base58check()),
+			Err(FromBase58Error::InvalidVersion { found: [0x30, 0, 0, 0], len: 21 }));
+	}
+
+	#[test]
+	fn test_script_pubkey() {
+		let genesis = BitcoinAddress::new(BitcoinNetwork::Mainnet, BitcoinAddressKind::P2pkh, GENESIS_HASH);
+		let mut expected = vec![0x76, 0xa9, 0x14];
+		expected.extend_from_slice(&GENESIS_HASH);
+		expected.extend_from_slice(&[0x88, 0xac]);
+		assert_eq!(genesis.script_pubkey(), expected);
+		assert_eq!(BitcoinAddress::from_script_pubkey(BitcoinNetwork::Mainnet, &expected), Some(genesis));
+
+		let address = BitcoinAddress::from_base58check("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy").unwrap();
+		let script = address.script_pubkey();
+		assert_eq!((script.len(), script[..2].to_vec(), script[22]), (23, vec![0xa9, 0x14], 0x87));
+		assert_eq!(&script[2..22], &address.hash[..]);
+		let testnet = BitcoinAddress::from_script_pubkey(BitcoinNetwork::Testnet, &script).unwrap();
+		assert_eq!(testnet.to_string(), "2N9hLwkSqr1cPQAPxbrGVUjxyjD11G2e1he");
+	}
+
+	#[test]
+	fn test_script_pubkey_unrecognized() {
+		let genesis = BitcoinAddress::new(BitcoinNetwork::Mainnet, BitcoinAddressKind::P2pkh, GENESIS_HASH);
+		let script = genesis.script_pubkey();
+		let recognize = |script: &[u8]| BitcoinAddress::from_script_pubkey(BitcoinNetwork::Mainnet, script);
+		assert_eq!(recognize(&script[..script.len() - 1]), None);
+		assert_eq!(recognize(&script[1..]), None);
+		assert_eq!(recognize(&[]), None);
+
+		// hash of 19 bytes behind a push of 20
+		let mut short = script.clone();
+		short.remove(3);
+		assert_eq!(recognize(&short), None);
+
+		// P2WPKH, OP_0 <20 bytes>
+		let mut segwit = vec![0x00, 0x14];
+		segwit.extend_from_slice(&GENESIS_HASH);
+		assert_eq!(recognize(&segwit), None);
+	}
+
+	#[cfg(feature = "hash160")]
+	#[test]
+	fn test_address_from_public_key_and_script() {
+		use alloc::vec::Vec;
+		use super::{public_key_address, script_address};
+		use {encode_versioned, hash160};
+
+		// the generator point of secp256k1
+		let compressed = [
+			0x02, 0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87, 0x0b, 0x07,
+			0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8, 0x17, 0x98,
+		];
+		let y = [
+			0x48, 0x3a, 0xda, 0x77, 0x26, 0xa3, 0xc4, 0x65, 0x5d, 0xa4, 0xfb, 0xfc, 0x0e, 0x11, 0x08, 0xa8,
+			0xfd, 0x17, 0xb4, 0x48, 0xa6, 0x85, 0x54, 0x19, 0x9c, 0x47, 0xd0, 0x8f, 0xfb, 0x10, 0xd4, 0xb8,
+		];
+		let mut uncompressed = vec![0x04];
+		uncompressed.extend_from_slice(&compressed[1..]);
+		uncompressed.extend_from_slice(&y);
+
+		let address = BitcoinAddress::from_public_key(BitcoinNetwork::Mainnet, &compressed).unwrap();
+		assert_eq!(address.to_string(), "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH");
+		let address = BitcoinAddress::from_public_key(BitcoinNetwork::Testnet, &compressed).unwrap();
+		assert_eq!(address.to_string(), "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r");
+		let address = BitcoinAddress::from_public_key(BitcoinNetwork::Mainnet, &uncompressed).unwrap();
+		assert_eq!(address.to_string(), "1EHNa6Q4Jz2uvNExL497mE43ikXhwF6kZm");
+
+		let key = |public_key: &[u8]| BitcoinAddress::from_public_key(BitcoinNetwork::Mainnet, public_key);
+		assert_eq!(key(b"hi"), None);
+		assert_eq!(key(&[]), None);
+		assert_eq!(key(&compressed[..32]), None);
+		assert_eq!(key(&uncompressed[..64]), None);
+		let mut wrong_prefix = compressed;
+		wrong_prefix[0] = 0x04;
+		assert_eq!(key(&wrong_prefix), None);
+		uncompressed[0] = 0x02;
+		assert_eq!(key(&uncompressed), None);
+
+		// 1-of-1 multisig, OP_1 <key> OP_1 OP_CHECKMULTISIG
+		let mut script: Vec<u8> = vec![0x51, 0x21];
+		script.extend_from_slice(&compressed);
+		script.extend_from_slice(&[0x51, 0xae]);
+		let address = BitcoinAddress::from_script(BitcoinNetwork::Mainnet, &script);
+		assert_eq!(address.to_string(), "3DicS6C8JZm59RsrgXr56iVHzYdQngiehV");
+		let address = BitcoinAddress::from_script(BitcoinNetwork::Testnet, &script);
+		assert_eq!(address.to_string(), "2N5GpVq89v2GRMDWQMfTwifUZCtqaczC6Y7");
+
+		// litecoin
+		assert_eq!(public_key_address(0x30, &compressed).unwrap(), "LVuDpNCSSj6pQ7t9Pv6d6sUkLKoqDEVUnJ");
+		assert_eq!(public_key_address(0x30, &compressed[..32]), None);
+		assert_eq!(public_key_address(0x30, &wrong_prefix), None);
+		assert_eq!(public_key_address(0x00, &compressed).unwrap(), "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH");
+		assert_eq!(script_address(0x05, &script), "3DicS6C8JZm59RsrgXr56iVHzYdQngiehV");
+		assert_eq!(script_address(0x32, &script), encode_versioned(&[0x32], &hash160(&script)));
+	}
+}
@@ -0,0 +1,210 @@
+//! Base58 alphabets
+
+use core::fmt;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use {FromBase58Error, ToBase58Error};
+use {decode, diagnostics, encode, validate, Diagnostics, Info};
+
+/// Errors that can occur when building a custom alphabet.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum AlphabetError {
+	/// The alphabet contained a byte which is not an ASCII character.
+	NonAsciiCharacter(u8, usize),
+	/// The alphabet contained the same character at two positions.
+	DuplicateCharacter(char, usize, usize),
+}
+
+impl fmt::Display for AlphabetError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			AlphabetError::NonAsciiCharacter(byte, i) =>
+				write!(f, "non-ASCII byte 0x{:02x} at position {} of the alphabet", byte, i),
+			AlphabetError::DuplicateCharacter(c, first, second) =>
+				write!(f, "character {:?} at positions {} and {} of the alphabet", c, first, second),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AlphabetError {}
+
+/// A base58 alphabet together with its reverse lookup table.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Alphabet {
+	encode: [u8; 58],
+	decode: [i8; 128],
+}
+
+impl Alphabet {
+	/// Bitcoin alphabet, also used by IPFS, Monero and Solana.
+	pub const BITCOIN: Alphabet = Alphabet::from_const(b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz");
+
+	/// Ripple (XRP Ledger) alphabet.
+	pub const RIPPLE: Alphabet = Alphabet::from_const(b"rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz");
+
+	/// Flickr short url alphabet.
+	pub const FLICKR: Alphabet = Alphabet::from_const(b"123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ");
+
+	/// Creates an alphabet from 58 distinct ASCII characters, the first of which encodes zero.
+	pub const fn new(alphabet: &[u8; 58]) -> Result<Alphabet, AlphabetError> {
+		let mut decode = [-1i8; 128];
+		let mut i = 0;
+		while i < alphabet.len() {
+			let c = alphabet[i];
+			if c >= 128 {
+				return Err(AlphabetError::NonAsciiCharacter(c, i));
+			}
+
+			if decode[c as usize] != -1 {
+				return Err(AlphabetError::DuplicateCharacter(c as char, decode[c as usize] as usize, i));
+			}
+
+			decode[c as usize] = i as i8;
+			i += 1;
+		}
+
+		Ok(Alphabet {
+			encode: *alphabet,
+			decode,
+		})
+	}
+
+	const fn from_const(alphabet: &[u8; 58]) -> Alphabet {
+		match Alphabet::new(alphabet) {
+			Ok(alphabet) => alphabet,
+			Err(_) => panic!("invalid base58 alphabet"),
+		}
+	}
+
+	/// Returns the characters of the alphabet, in digit order.
+	pub fn as_str(&self) -> &str {
+		// the constructor accepts only ASCII characters
+		core::str::from_utf8(&self.encode).unwrap_or_default()
+	}
+
+	/// Converts `input` to a base58 string using this alphabet.
+	#[cfg(feature = "alloc")]
+	pub fn encode(&self, input: &[u8]) -> String {
+		encode::encode(input, self)
+	}
+
+	/// Writes `input` as base58 characters of this alphabet into `output`, returning the number of bytes written.
+	///
+	/// The whole `output` buffer may be used as scratch space, see [`encode_into`](::encode_into).
+	pub fn encode_into(&self, input: &[u8], output: &mut [u8]) -> Result<usize, ToBase58Error> {
+		encode::encode_into_with(input, output, self)
+	}
+
+	/// Writes the `N` bytes of `input` as base58 characters of this alphabet into `output`, see
+	/// [`encode_array`](::encode_array).
+	pub fn encode_array<const N: usize>(&self, input: &[u8; N], output: &mut [u8]) -> Result<usize, ToBase58Error> {
+		encode::encode_array_with(input, output, self)
+	}
+
+	/// Converts a base58 string written in this alphabet into an owned vector of bytes.
+	#[cfg(feature = "alloc")]
+	pub fn decode(&self, input: &str) -> Result<Vec<u8>, FromBase58Error> {
+		decode::decode(input, self)
+	}
+
+	/// Writes the bytes encoded by a base58 string of this alphabet into `output`, returning the number of
+	/// bytes written.
+	///
+	/// The whole `output` buffer may be used as scratch space, see [`decode_into`](::decode_into).
+	pub fn decode_into(&self, input: &str, output: &mut [u8]) -> Result<usize, FromBase58Error> {
+		decode::decode_into_with(input, output, self)
+	}
+
+	/// Decodes a base58 string of this alphabet into an array of exactly `N` bytes,
+	/// see [`decode_array`](::decode_array).
+	pub fn decode_array<const N: usize>(&self, input: &str) -> Result<[u8; N], FromBase58Error> {
+		decode::decode_array_with(input, self)
+	}
+
+	/// Checks that `input` consists of characters of this alphabet only, see [`validate`](::validate).
+	pub fn validate(&self, input: &str) -> Result<Info, FromBase58Error> {
+		validate::validate_with(input, self)
+	}
+
+	/// Returns an iterator over every character of `input` which is not a part of this alphabet,
+	/// see [`diagnose`](::diagnose).
+	pub fn diagnose<'a>(&'a self, input: &'a str) -> Diagnostics<'a> {
+		diagnostics::diagnose_with(input, self)
+	}
+
+	/// Returns the character encoding `digit`.
+	#[inline]
+	pub(crate) fn char(&self, digit: u8) -> u8 {
+		self.encode[digit as usize]
+	}
+
+	/// Returns the value of the base58 digit `c`, if it is a part of the alphabet.
+	#[inline]
+	pub(crate) fn digit(&self, c: u8) -> Option<u8> {
+		match self.decode.get(c as usize) {
+			Some(&digit) if digit >= 0 => Some(digit as u8),
+			_ => None,
+		}
+	}
+
+	/// Returns whether `c` is a part of the alphabet.
+	#[inline]
+	pub(crate) fn contains(&self, c: char) -> bool {
+		c.is_ascii() && self.digit(c as u8).is_some()
+	}
+}
+
+impl fmt::Debug for Alphabet {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_tuple("Alphabet").field(&self.as_str()).finish()
+	}
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+	use super::{Alphabet, AlphabetError};
+	use {ToBase58, FromBase58};
+
+	#[test]
+	fn test_bitcoin_alphabet_matches_traits() {
+		assert_eq!(Alphabet::BITCOIN.encode(b"\0abc"), b"\0abc".to_base58());
+		assert_eq!(Alphabet::BITCOIN.decode("1ZiCa"), "1ZiCa".from_base58());
+	}
+
+	#[test]
+	fn test_ripple_alphabet() {
+		// XRP Ledger genesis account, AccountID with its 0x00 type prefix and checksum
+		let account = [
+			0x00, 0xb5, 0xf7, 0x62, 0x79, 0x8a, 0x53, 0xd5, 0x43, 0xa0, 0x14, 0xca, 0xf8, 0xb2,
+			0x97, 0xcf, 0xf8, 0xf2, 0xf9, 0x37, 0xe8, 0xbf, 0x32, 0xba, 0x9f,
+		];
+		assert_eq!(Alphabet::RIPPLE.encode(&account), "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+		assert_eq!(Alphabet::RIPPLE.decode("rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh").unwrap(), &account[..]);
+	}
+
+	#[test]
+	fn test_flickr_alphabet() {
+		assert_eq!(Alphabet::FLICKR.encode(b"\0abc"), "1yHcz");
+		assert_eq!(Alphabet::FLICKR.decode("1yHcz").unwrap(), b"\0abc");
+		assert_ne!(Alphabet::FLICKR.decode("1ZiCa"), Alphabet::BITCOIN.decode("1ZiCa"));
+	}
+
+	#[test]
+	fn test_custom_alphabet() {
+		let mut chars = *b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+		chars.reverse();
+		let alphabet = Alphabet::new(&chars).unwrap();
+		assert_eq!(alphabet.as_str().as_bytes(), &chars[..]);
+		assert_eq!(alphabet.encode(b"\0\0\x01"), "zzy");
+		assert_eq!(alphabet.decode("zzy").unwrap(), b"\0\0\x01");
+
+		chars[10] = b'z';
+		assert_eq!(Alphabet::new(&chars), Err(AlphabetError::DuplicateCharacter('z', 0, 10)));
+		chars[10] = 0xc3;
+		assert_eq!(Alphabet::new(&chars), Err(AlphabetError::NonAsciiCharacter(0xc3, 10)));
+	}
+}
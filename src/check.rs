@@ -0,0 +1,214 @@
+//! Base58Check encoding, base58 with a 4-byte double SHA-256 checksum appended
+
+use alloc::vec::Vec;
+use alloc::string::String;
+use sha256::double_sha256;
+use {ToBase58, FromBase58, FromBase58Error};
+
+/// Number of checksum bytes appended to the payload.
+pub const CHECKSUM_LEN: usize = 4;
+
+/// A trait for converting a value to base58check encoded string.
+pub trait ToBase58Check {
+	/// Appends the checksum to a value of `self` and converts it to a base58 value, returning the owned string.
+	fn to_base58check(&self) -> String;
+}
+
+/// A trait for converting base58check encoded values.
+#[allow(clippy::wrong_self_convention)]
+pub trait FromBase58Check {
+	/// Convert a value of `self`, interpreted as base58check encoded data, into an owned vector of bytes,
+	/// verifying and stripping the checksum.
+	fn from_base58check(&self) -> Result<Vec<u8>, FromBase58Error>;
+}
+
+impl ToBase58Check for [u8] {
+	fn to_base58check(&self) -> String {
+		let mut data = Vec::with_capacity(self.len() + CHECKSUM_LEN);
+		data.extend_from_slice(self);
+		data.extend_from_slice(&checksum(self));
+		data.to_base58()
+	}
+}
+
+impl FromBase58Check for str {
+	fn from_base58check(&self) -> Result<Vec<u8>, FromBase58Error> {
+		let mut data = self.from_base58()?;
+		let payload_len = verify_checksum(&data)?;
+		data.truncate(payload_len);
+		Ok(data)
+	}
+}
+
+/// A base58check payload split into its version prefix and the data that follows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Versioned {
+	/// Version prefix, e.g. 1 byte for bitcoin, 2 bytes for zcash transparent addresses.
+	pub version: Vec<u8>,
+	/// Data following the version prefix, without the checksum.
+	pub payload: Vec<u8>,
+}
+
+impl Versioned {
+	/// Creates a new versioned payload.
+	pub fn new(version: &[u8], payload: &[u8]) -> Self {
+		Versioned {
+			version: version.to_vec(),
+			payload: payload.to_vec(),
+		}
+	}
+
+	/// Converts the version followed by the payload to a base58check string.
+	pub fn to_base58check(&self) -> String {
+		encode_versioned(&self.version, &self.payload)
+	}
+
+	/// Decodes a base58check string, splitting off the first `version_len` bytes as the version.
+	///
+	/// Data shorter than the version fails with [`FromBase58Error::UnexpectedLength`].
+	pub fn from_base58check(input: &str, version_len: usize) -> Result<Self, FromBase58Error> {
+		let data = input.from_base58check()?;
+		if data.len() < version_len {
+			return Err(FromBase58Error::UnexpectedLength { expected: version_len, actual: data.len() });
+		}
+
+		Ok(Versioned {
+			version: data[..version_len].to_vec(),
+			payload: data[version_len..].to_vec(),
+		})
+	}
+
+	/// Decodes a base58check string which must start with one of `versions`.
+	///
+	/// Versions are tried in order and the first one which is a prefix of the decoded data is split off.
+	/// If `payload_len` is given, the remaining payload must have exactly that length.
+	pub fn from_base58check_with(
+		input: &str,
+		versions: &[&[u8]],
+		payload_len: Option<usize>
+	) -> Result<Self, FromBase58Error> {
+		let data = input.from_base58check()?;
+		let version = versions.iter()
+			.find(|version| data.starts_with(version))
+			.ok_or_else(|| FromBase58Error::invalid_version(&data))?;
+
+		let payload = &data[version.len()..];
+		match payload_len {
+			Some(expected) if expected != payload.len() => Err(FromBase58Error::UnexpectedLength {
+				expected,
+				actual: payload.len(),
+			}),
+			_ => Ok(Versioned::new(version, payload)),
+		}
+	}
+}
+
+/// Converts `version` followed by `payload` to a base58check string.
+pub fn encode_versioned(version: &[u8], payload: &[u8]) -> String {
+	let mut data = Vec::with_capacity(version.len() + payload.len());
+	data.extend_from_slice(version);
+	data.extend_from_slice(payload);
+	data.to_base58check()
+}
+
+/// Returns the first 4 bytes of the double SHA-256 of `payload`.
+pub(crate) fn checksum(payload: &[u8]) -> [u8; CHECKSUM_LEN] {
+	let hash = double_sha256(payload);
+	[hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Verifies the checksum at the end of `data`, returning the length of the payload in front of it.
+pub(crate) fn verify_checksum(data: &[u8]) -> Result<usize, FromBase58Error> {
+	let payload_len = data.len().checked_sub(CHECKSUM_LEN).ok_or(FromBase58Error::InvalidBase58Length)?;
+	let expected = checksum(&data[..payload_len]);
+	let actual = [data[payload_len], data[payload_len + 1], data[payload_len + 2], data[payload_len + 3]];
+	if expected != actual {
+		return Err(FromBase58Error::InvalidChecksum { expected, actual });
+	}
+
+	Ok(payload_len)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ToBase58Check, FromBase58Check, Versioned, encode_versioned};
+	use FromBase58Error;
+
+	const ZCASH_T1: &[u8] = &[0x1c, 0xb8];
+	const ZCASH_T3: &[u8] = &[0x1c, 0xbd];
+	const TEZOS_TZ1: &[u8] = &[0x06, 0xa1, 0x9f];
+	const TEZOS_EDSK: &[u8] = &[0x2b, 0xf6, 0x4e, 0x07];
+
+	#[test]
+	fn test_to_base58check() {
+		assert_eq!(b"".to_base58check(), "3QJmnh");
+		assert_eq!(b"abc".to_base58check(), "4h3c6RH52R");
+		// bitcoin genesis block coinbase address
+		let address = [
+			0x00, 0x62, 0xe9, 0x07, 0xb1, 0x5c, 0xbf, 0x27, 0xd5, 0x42, 0x53, 0x99, 0xeb, 0xf6, 0xf0, 0xfb,
+			0x50, 0xeb, 0xb8, 0x8f, 0x18,
+		];
+		assert_eq!(address.to_base58check(), "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+	}
+
+	#[test]
+	fn test_from_base58check() {
+		assert_eq!("3QJmnh".from_base58check().unwrap(), b"");
+		assert_eq!("4h3c6RH52R".from_base58check().unwrap(), b"abc");
+		assert_eq!("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".from_base58check().unwrap()[..3], [0x00, 0x62, 0xe9]);
+	}
+
+	#[test]
+	fn test_from_base58check_invalid() {
+		assert_eq!("".from_base58check(), Err(FromBase58Error::InvalidBase58Length));
+		assert_eq!("ZiCa".from_base58check(), Err(FromBase58Error::InvalidBase58Length));
+		assert_eq!("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfN0".from_base58check(),
+			Err(FromBase58Error::InvalidBase58Character { character: '0', byte_offset: 33, char_index: 33 }));
+		assert_eq!("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb".from_base58check(),
+			Err(FromBase58Error::InvalidChecksum {
+				expected: [0xc2, 0x9b, 0x7d, 0x93],
+				actual: [0xc2, 0x9b, 0x7d, 0x94],
+			}));
+	}
+
+	#[test]
+	fn test_encode_versioned() {
+		assert_eq!(encode_versioned(&[0x00], &[
+			0x62, 0xe9, 0x07, 0xb1, 0x5c, 0xbf, 0x27, 0xd5, 0x42, 0x53, 0x99, 0xeb, 0xf6, 0xf0, 0xfb, 0x50,
+			0xeb, 0xb8, 0x8f, 0x18,
+		]), "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+		assert_eq!(encode_versioned(ZCASH_T1, &[0x11; 20]), "t1KRqwQhktLV4BjbNLiuH6pb3AMoszZKcQB");
+		assert_eq!(Versioned::new(ZCASH_T1, &[0x11; 20]).to_base58check(), "t1KRqwQhktLV4BjbNLiuH6pb3AMoszZKcQB");
+	}
+
+	#[test]
+	fn test_decode_versioned() {
+		let decoded = Versioned::from_base58check("t1KRqwQhktLV4BjbNLiuH6pb3AMoszZKcQB", 2).unwrap();
+		assert_eq!(decoded, Versioned::new(ZCASH_T1, &[0x11; 20]));
+
+		let decoded = Versioned::from_base58check("tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx", 3).unwrap();
+		assert_eq!(decoded.version, TEZOS_TZ1);
+		assert_eq!(decoded.payload.len(), 20);
+
+		assert_eq!(Versioned::from_base58check("3QJmnh", 0).unwrap(), Versioned::new(&[], &[]));
+		assert_eq!(Versioned::from_base58check("3QJmnh", 1),
+			Err(FromBase58Error::UnexpectedLength { expected: 1, actual: 0 }));
+	}
+
+	#[test]
+	fn test_decode_versioned_with() {
+		let tezos = [TEZOS_EDSK, TEZOS_TZ1];
+		let decoded = Versioned::from_base58check_with("tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx", &tezos, Some(20));
+		assert_eq!(decoded.unwrap().version, TEZOS_TZ1);
+
+		let zcash = [ZCASH_T1, ZCASH_T3];
+		let decoded = Versioned::from_base58check_with("t1KRqwQhktLV4BjbNLiuH6pb3AMoszZKcQB", &zcash, Some(20));
+		assert_eq!(decoded.unwrap().version, ZCASH_T1);
+		assert_eq!(Versioned::from_base58check_with("t1KRqwQhktLV4BjbNLiuH6pb3AMoszZKcQB", &zcash[1..], Some(20)),
+			Err(FromBase58Error::InvalidVersion { found: [0x1c, 0xb8, 0x11, 0x11], len: 22 }));
+		assert_eq!(Versioned::from_base58check_with("t1KRqwQhktLV4BjbNLiuH6pb3AMoszZKcQB", &zcash, Some(32)),
+			Err(FromBase58Error::UnexpectedLength { expected: 32, actual: 20 }));
+		assert_eq!(Versioned::from_base58check_with("tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx", &zcash, None),
+			Err(FromBase58Error::InvalidVersion { found: [0x06, 0xa1, 0x9f, 0x02], len: 23 }));
+	}
+}
@@ -0,0 +1,258 @@
+//! Recovery of mistyped base58check strings using the checksum
+
+use alloc::vec::Vec;
+use alloc::string::String;
+use decode::invalid_character;
+use {Alphabet, FromBase58Check, FromBase58Error};
+
+/// A single edit turning a mistyped base58check string into one whose checksum is valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+	/// The character at `position` was replaced.
+	Substitution {
+		/// Character index of the replaced character.
+		position: usize,
+		/// Character found in the input.
+		from: char,
+		/// Character of the candidate.
+		to: char,
+	},
+	/// The characters at `position` and `position + 1` were swapped.
+	Transposition {
+		/// Character index of the first swapped character.
+		position: usize,
+	},
+	/// A missing character was inserted before `position`.
+	Insertion {
+		/// Character index the character was inserted at.
+		position: usize,
+		/// The inserted character.
+		character: char,
+	},
+	/// An extra character was removed.
+	Deletion {
+		/// Character index of the removed character.
+		position: usize,
+		/// The removed character.
+		character: char,
+	},
+}
+
+/// A candidate base58check string and the edit which produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Correction {
+	/// The corrected string, with a valid checksum.
+	pub candidate: String,
+	/// The edit applied to the input.
+	pub edit: Edit,
+}
+
+/// Searches every single-character substitution, adjacent transposition, insertion and deletion of `input`
+/// and returns the candidates whose base58check checksum validates.
+///
+/// A valid `input` is not corrected and yields no candidates. Every extra candidate has a chance of about
+/// 2^-32 to validate by accident, so a result with more than one candidate is ambiguous.
+///
+/// An input of `n` characters has at most `118 * n + 57` candidates, each costing a decode and a checksum, so
+/// inputs with more are rejected with [`FromBase58Error::SearchSpaceTooLarge`] instead of being searched.
+pub fn correct_base58check(input: &str, limit: usize) -> Result<Vec<Correction>, FromBase58Error> {
+	let mut corrections = Vec::new();
+	if input.from_base58check().is_ok() {
+		return Ok(corrections);
+	}
+
+	let chars: Vec<char> = input.chars().collect();
+	// 58 * n substitutions, n - 1 transpositions, 58 * (n + 1) insertions and n deletions
+	let candidates = chars.len().saturating_mul(118).saturating_add(57);
+	if candidates > limit {
+		return Err(FromBase58Error::SearchSpaceTooLarge { ambiguous: chars.len(), candidates, limit });
+	}
+
+	let alphabet = Alphabet::BITCOIN.as_str();
+	let mut candidate = String::with_capacity(input.len() + 1);
+	let mut try_candidate = |candidate: &String, edit: Edit| {
+		if candidate.from_base58check().is_ok() && corrections.iter().all(|c: &Correction| c.candidate != *candidate) {
+			corrections.push(Correction { candidate: candidate.clone(), edit });
+		}
+	};
+
+	for position in 0..chars.len() {
+		for to in alphabet.chars().filter(|c| *c != chars[position]) {
+			build(&mut candidate, &chars[..position], Some(to), &chars[position + 1..]);
+			try_candidate(&candidate, Edit::Substitution { position, from: chars[position], to });
+		}
+	}
+
+	for position in 0..chars.len().saturating_sub(1) {
+		if chars[position] != chars[position + 1] {
+			let swapped = [chars[position + 1], chars[position]];
+			candidate.clear();
+			candidate.extend(chars[..position].iter().chain(&swapped).chain(&chars[position + 2..]));
+			try_candidate(&candidate, Edit::Transposition { position });
+		}
+	}
+
+	for position in 0..=chars.len() {
+		for character in alphabet.chars() {
+			build(&mut candidate, &chars[..position], Some(character), &chars[position..]);
+			try_candidate(&candidate, Edit::Insertion { position, character });
+		}
+	}
+
+	for position in 0..chars.len() {
+		build(&mut candidate, &chars[..position], None, &chars[position + 1..]);
+		try_candidate(&candidate, Edit::Deletion { position, character: chars[position] });
+	}
+
+	Ok(corrections)
+}
+
+/// Restores the letter case of a base58check string whose case was lost, e.g. by uppercasing, returning every
+/// combination of cases whose checksum validates.
+///
+/// Letters valid in both cases double the search space, which is rejected with
+/// [`FromBase58Error::SearchSpaceTooLarge`] if it would need more than `limit` checksum verifications.
+pub fn recover_case_base58check(input: &str, limit: usize) -> Result<Vec<String>, FromBase58Error> {
+	let alphabet = &Alphabet::BITCOIN;
+
+	// every character is either fixed, or a letter that could have been either case
+	let mut chars = Vec::with_capacity(input.len());
+	let mut ambiguous = Vec::new();
+	for (byte_offset, c) in input.char_indices() {
+		let (lower, upper) = (c.to_ascii_lowercase(), c.to_ascii_uppercase());
+		match (alphabet.contains(lower), alphabet.contains(upper)) {
+			(true, true) if lower != upper => {
+				ambiguous.push(chars.len());
+				chars.push(lower);
+			},
+			(true, _) => chars.push(lower),
+			(false, true) => chars.push(upper),
+			(false, false) => return Err(invalid_character(input, byte_offset)),
+		}
+	}
+
+	let combinations = 1usize.checked_shl(ambiguous.len() as u32).unwrap_or(usize::MAX);
+	if combinations > limit {
+		return Err(FromBase58Error::SearchSpaceTooLarge {
+			ambiguous: ambiguous.len(),
+			candidates: combinations,
+			limit,
+		});
+	}
+
+	let mut found = Vec::new();
+	let mut candidate = String::with_capacity(input.len());
+	for mask in 0..combinations {
+		for (bit, position) in ambiguous.iter().enumerate() {
+			chars[*position] = match mask & (1 << bit) {
+				0 => chars[*position].to_ascii_lowercase(),
+				_ => chars[*position].to_ascii_uppercase(),
+			};
+		}
+
+		candidate.clear();
+		candidate.extend(chars.iter());
+		if candidate.from_base58check().is_ok() {
+			found.push(candidate.clone());
+		}
+	}
+
+	Ok(found)
+}
+
+fn build(candidate: &mut String, head: &[char], middle: Option<char>, tail: &[char]) {
+	candidate.clear();
+	candidate.extend(head.iter().chain(middle.as_ref()).chain(tail));
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{correct_base58check, recover_case_base58check, Correction, Edit};
+	use FromBase58Error;
+
+	const ADDRESS: &str = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa";
+	const LIMIT: usize = 1 << 16;
+
+	fn corrected(input: &str) -> Correction {
+		let corrections = correct_base58check(input, LIMIT).unwrap();
+		assert_eq!(corrections.len(), 1, "{:?}", corrections);
+		assert_eq!(corrections[0].candidate, ADDRESS);
+		corrections[0].clone()
+	}
+
+	#[test]
+	fn test_correct_valid_input() {
+		assert_eq!(correct_base58check(ADDRESS, LIMIT), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_correct_substitution() {
+		assert_eq!(corrected("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb").edit,
+			Edit::Substitution { position: 33, from: 'b', to: 'a' });
+		assert_eq!(corrected("1A1zP1eP5QGefi2DMPTfTLSSLmv7DivfNa").edit,
+			Edit::Substitution { position: 22, from: 'S', to: '5' });
+		// invalid characters are substituted too
+		assert_eq!(corrected("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfN0").edit,
+			Edit::Substitution { position: 33, from: '0', to: 'a' });
+	}
+
+	#[test]
+	fn test_correct_transposition() {
+		assert_eq!(corrected("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivNfa").edit, Edit::Transposition { position: 31 });
+		assert_eq!(corrected("A11zP1eP5QGefi2DMPTfTL5SLmv7DivfNa").edit, Edit::Transposition { position: 0 });
+	}
+
+	#[test]
+	fn test_correct_insertion_and_deletion() {
+		assert_eq!(corrected("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfN").edit,
+			Edit::Insertion { position: 33, character: 'a' });
+		assert_eq!(corrected("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNNa").edit,
+			Edit::Deletion { position: 32, character: 'N' });
+		assert_eq!(corrected("1A1zP1eP5QGefi2DM PTfTL5SLmv7DivfNa").edit,
+			Edit::Deletion { position: 17, character: ' ' });
+	}
+
+	#[test]
+	fn test_correct_no_candidates() {
+		// two typos are out of reach
+		assert_eq!(correct_base58check("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfXX", LIMIT), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_correct_limit() {
+		// 34 characters have 118 * 34 + 57 = 4069 candidates
+		let input = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb";
+		assert_eq!(correct_base58check(input, 4069).unwrap().len(), 1);
+		assert_eq!(correct_base58check(input, 4068),
+			Err(FromBase58Error::SearchSpaceTooLarge { ambiguous: 34, candidates: 4069, limit: 4068 }));
+		assert_eq!(correct_base58check(&"z".repeat(1000), LIMIT),
+			Err(FromBase58Error::SearchSpaceTooLarge { ambiguous: 1000, candidates: 118_057, limit: LIMIT }));
+		// the limit does not apply to valid inputs, which are not searched
+		assert_eq!(correct_base58check(ADDRESS, 0), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_recover_case() {
+		// few letters keep the search space small, 'o' and 'L' only exist in one case
+		let original = "11111114LRnoA69mE";
+		assert_eq!(recover_case_base58check(original, 1 << 16), Ok(vec![original.into()]));
+		assert_eq!(recover_case_base58check(&original.to_uppercase(), 1 << 16), Ok(vec![original.into()]));
+		assert_eq!(recover_case_base58check(&original.to_lowercase(), 1 << 16), Ok(vec![original.into()]));
+		assert_eq!(recover_case_base58check("", 1), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_recover_case_limit() {
+		assert_eq!(recover_case_base58check(&ADDRESS.to_uppercase(), 1 << 16),
+			Err(FromBase58Error::SearchSpaceTooLarge { ambiguous: 23, candidates: 1 << 23, limit: 1 << 16 }));
+		assert_eq!(recover_case_base58check("ZICA", 3),
+			Err(FromBase58Error::SearchSpaceTooLarge { ambiguous: 3, candidates: 8, limit: 3 }));
+		assert_eq!(recover_case_base58check(&"Z".repeat(100), 1 << 16),
+			Err(FromBase58Error::SearchSpaceTooLarge { ambiguous: 100, candidates: usize::MAX, limit: 1 << 16 }));
+		assert_eq!(recover_case_base58check("ZI-CA", 1 << 16), Err(FromBase58Error::InvalidBase58Character {
+			character: '-',
+			byte_offset: 2,
+			char_index: 2,
+		}));
+	}
+}
@@ -0,0 +1,417 @@
+//! Base58 decoding
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use large;
+use validate::validate_with;
+use {Alphabet, FromBase58Error};
+
+/// Number of characters consumed per pass over the limbs, 58^5 times a limb still fits in a u64.
+const LIMB_DIGITS: usize = 5;
+
+/// Longest array decoded with arithmetic specialized for its length by [`decode_array`].
+const FIXED_MAX_LEN: usize = 64;
+
+/// Inputs decoding to at most this many bytes more than expected have their exact length reported.
+const EXACT_LEN_SLACK: usize = 8;
+
+/// Powers of 58 up to 58^5.
+const POW58: [u64; LIMB_DIGITS + 1] = [1, 58, 58 * 58, 58 * 58 * 58, 58 * 58 * 58 * 58, 58 * 58 * 58 * 58 * 58];
+
+/// Writes the bytes encoded by the base58 string `input` into `output`, returning the number of bytes written.
+///
+/// No allocation is made, `output` is used as scratch space and its contents after the returned length
+/// are unspecified. If `output` is too small, the error reports a buffer size that is large enough.
+pub fn decode_into(input: &str, output: &mut [u8]) -> Result<usize, FromBase58Error> {
+	decode_into_with(input, output, &Alphabet::BITCOIN)
+}
+
+pub(crate) fn decode_into_with(input: &str, output: &mut [u8], alphabet: &Alphabet) -> Result<usize, FromBase58Error> {
+	let b58 = input.as_bytes();
+	if let Some(i) = b58.iter().position(|c| alphabet.digit(*c).is_none()) {
+		// Invalid base58 digit
+		return Err(invalid_character(input, i));
+	}
+
+	let zcount = b58.iter().take_while(|x| **x == alphabet.char(0)).count();
+	if output.len() < zcount {
+		return Err(buffer_too_small(b58.len(), zcount));
+	}
+
+	// the number is built right-aligned in the space left after the leading zeros, in big-endian u32 limbs
+	// and, once every whole limb is used, the `head` bytes in front of them
+	let (zeros, number) = output.split_at_mut(zcount);
+	let size = number.len();
+	let head = size % 4;
+	let limbs = size / 4;
+	let mut used = 0;
+	let mut top = 0;
+
+	for group in b58[zcount..].chunks(LIMB_DIGITS) {
+		let mut carry = group.iter().fold(0u64, |acc, c| acc * 58 + alphabet.digit(*c).unwrap_or(0) as u64);
+		let factor = POW58[group.len()];
+		for i in 0..used {
+			carry += get_limb(number, i) as u64 * factor;
+			set_limb(number, i, carry as u32);
+			carry >>= 32;
+		}
+
+		if used < limbs {
+			// carry < factor, so it fits in a single new limb
+			if carry != 0 {
+				set_limb(number, used, carry as u32);
+				used += 1;
+			}
+			continue;
+		}
+
+		for byte in number[head - top..head].iter_mut().rev() {
+			carry += *byte as u64 * factor;
+			*byte = carry as u8;
+			carry >>= 8;
+		}
+
+		while carry != 0 {
+			if top == head {
+				return Err(buffer_too_small(b58.len(), zcount));
+			}
+
+			top += 1;
+			number[head - top] = carry as u8;
+			carry >>= 8;
+		}
+	}
+
+	// the most significant limb may start with zero bytes
+	let start = size - 4 * used - top;
+	let len = size - start - number[start..].iter().take_while(|x| **x == 0).count();
+	number.copy_within(size - len.., 0);
+	for zero in zeros.iter_mut() {
+		*zero = 0;
+	}
+
+	Ok(zcount + len)
+}
+
+fn get_limb(number: &[u8], i: usize) -> u32 {
+	let end = number.len() - 4 * i;
+	u32::from_be_bytes([number[end - 4], number[end - 3], number[end - 2], number[end - 1]])
+}
+
+fn set_limb(number: &mut [u8], i: usize, limb: u32) {
+	let end = number.len() - 4 * i;
+	number[end - 4..end].copy_from_slice(&limb.to_be_bytes());
+}
+
+/// Returns the error for the invalid character starting at byte `offset` of `input`.
+pub(crate) fn invalid_character(input: &str, offset: usize) -> FromBase58Error {
+	FromBase58Error::InvalidBase58Character {
+		character: input[offset..].chars().next().unwrap_or_default(),
+		byte_offset: offset,
+		char_index: input[..offset].chars().count(),
+	}
+}
+
+/// Decodes the base58 string `input` into an array of exactly `N` bytes.
+///
+/// Inputs decoding to fewer or more bytes, including inputs with extra leading '1's, are rejected with
+/// [`FromBase58Error::UnexpectedLength`]. Up to 64 bytes the limb arithmetic is specialized for the length
+/// known at compile time, which makes this faster than [`decode_into`] for hashes, keys and signatures.
+pub fn decode_array<const N: usize>(input: &str) -> Result<[u8; N], FromBase58Error> {
+	decode_array_with(input, &Alphabet::BITCOIN)
+}
+
+pub(crate) fn decode_array_with<const N: usize>(input: &str, alphabet: &Alphabet) -> Result<[u8; N], FromBase58Error> {
+	if N > FIXED_MAX_LEN {
+		return decode_array_slow(input, alphabet);
+	}
+
+	// little-endian limbs, every pass goes over all of them
+	let b58 = input.as_bytes();
+	let zcount = b58.iter().take_while(|x| **x == alphabet.char(0)).count();
+	let mut limbs = [0u32; FIXED_MAX_LEN / 4];
+	let limbs = &mut limbs[..N.div_ceil(4)];
+	for (i, group) in b58[zcount..].chunks(LIMB_DIGITS).enumerate() {
+		let mut carry = 0;
+		for (j, c) in group.iter().enumerate() {
+			match alphabet.digit(*c) {
+				Some(digit) => carry = carry * 58 + digit as u64,
+				None => return Err(invalid_character(input, zcount + i * LIMB_DIGITS + j)),
+			}
+		}
+
+		let factor = POW58[group.len()];
+		for limb in limbs.iter_mut() {
+			carry += *limb as u64 * factor;
+			*limb = carry as u32;
+			carry >>= 32;
+		}
+
+		if carry != 0 {
+			// too long, the slow path reports the first invalid character or the length
+			return decode_array_slow(input, alphabet);
+		}
+	}
+
+	let mut number = [0u8; FIXED_MAX_LEN];
+	let number = &mut number[..limbs.len() * 4];
+	for (bytes, limb) in number.rchunks_exact_mut(4).zip(limbs.iter()) {
+		bytes.copy_from_slice(&limb.to_be_bytes());
+	}
+
+	let significant = number.len() - number.iter().take_while(|x| **x == 0).count();
+	if zcount + significant != N {
+		// the slow path counts the length for the error
+		return decode_array_slow(input, alphabet);
+	}
+
+	let mut output = [0u8; N];
+	output[zcount..].copy_from_slice(&number[number.len() - significant..]);
+	Ok(output)
+}
+
+fn decode_array_slow<const N: usize>(input: &str, alphabet: &Alphabet) -> Result<[u8; N], FromBase58Error> {
+	let mut output = [0u8; N];
+	match decode_into_with(input, &mut output, alphabet) {
+		Ok(len) if len == N => Ok(output),
+		Ok(actual) => Err(FromBase58Error::UnexpectedLength { expected: N, actual }),
+		Err(FromBase58Error::BufferTooSmall { .. }) => Err(too_long::<N>(input, alphabet)),
+		Err(err) => Err(err),
+	}
+}
+
+/// Returns the error for a valid `input` which does not fit in `N` bytes.
+///
+/// The length is exact when the number of digits tells it, or when the input decodes to at most
+/// [`EXACT_LEN_SLACK`] bytes more than `N`, which fit on the stack. Longer inputs fail with
+/// [`FromBase58Error::TooLong`], so rejecting them does not cost work or memory proportional to the input.
+fn too_long<const N: usize>(input: &str, alphabet: &Alphabet) -> FromBase58Error {
+	// usually the leading zeros are what does not fit
+	let zcount = input.bytes().take_while(|x| *x == alphabet.char(0)).count();
+	if let Ok(len) = decode_into_with(&input[zcount..], &mut [0u8; N], alphabet) {
+		return FromBase58Error::UnexpectedLength { expected: N, actual: zcount + len };
+	}
+
+	let info = match validate_with(input, alphabet) {
+		Ok(info) => info,
+		Err(err) => return err,
+	};
+	if info.min_decoded_len == info.max_decoded_len {
+		return FromBase58Error::UnexpectedLength { expected: N, actual: info.max_decoded_len };
+	}
+	if info.max_decoded_len > N + EXACT_LEN_SLACK {
+		return FromBase58Error::TooLong { expected: N, at_least: info.min_decoded_len.max(N + 1) };
+	}
+
+	// at least N + EXACT_LEN_SLACK bytes
+	let mut large = [[0u8; N]; 2];
+	let mut small = [0u8; 2 * EXACT_LEN_SLACK];
+	let scratch = if N >= EXACT_LEN_SLACK { large.as_flattened_mut() } else { &mut small[..] };
+	match decode_into_with(input, scratch, alphabet) {
+		Ok(actual) => FromBase58Error::UnexpectedLength { expected: N, actual },
+		Err(err) => err,
+	}
+}
+
+/// Every leading zero digit is a single zero byte, every other character carries
+/// log(58) / log(256) ~= 0.733 bytes.
+///
+/// Computed in parts so that it does not overflow, the result is at most `len + 1`.
+fn buffer_too_small(len: usize, zcount: usize) -> FromBase58Error {
+	let required = match len - zcount {
+		0 => zcount,
+		digits => digits / 1000 * 733 + digits % 1000 * 733 / 1000 + zcount + 1,
+	};
+
+	FromBase58Error::BufferTooSmall { required }
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn decode(input: &str, alphabet: &Alphabet) -> Result<Vec<u8>, FromBase58Error> {
+	if input.len() >= large::THRESHOLD {
+		return large::decode(input, alphabet);
+	}
+
+	// no character decodes to more than one byte
+	let mut output = vec![0u8; input.len()];
+	let len = decode_into_with(input, &mut output, alphabet)?;
+	output.truncate(len);
+	Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{decode_into, decode_array, buffer_too_small};
+	#[cfg(feature = "alloc")]
+	use alloc::string::String;
+	#[cfg(feature = "alloc")]
+	use test_util::with_zeros;
+	#[cfg(feature = "alloc")]
+	use {validate, ToBase58, FromBase58, TryFromBase58};
+	use FromBase58Error;
+
+	#[test]
+	fn test_decode_into() {
+		let mut output = [0xffu8; 64];
+		assert_eq!(decode_into("", &mut output), Ok(0));
+		assert_eq!(decode_into("ZiCa", &mut output), Ok(3));
+		assert_eq!(&output[..3], b"abc");
+		assert_eq!(decode_into("11ZiCa", &mut output), Ok(5));
+		assert_eq!(&output[..5], b"\0\0abc");
+		assert_eq!(decode_into("111", &mut output), Ok(3));
+		assert_eq!(&output[..3], b"\0\0\0");
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_decode_into_matches_from_base58() {
+		let mut output = [0u8; 256];
+		for len in 0..256 {
+			let input: [u8; 256] = with_zeros(len / 8, len);
+			let encoded = input[..len].to_base58();
+			assert_eq!(decode_into(&encoded, &mut output[..len]), Ok(len));
+			assert_eq!(&output[..len], &input[..len]);
+			assert_eq!(encoded.from_base58().unwrap(), &input[..len]);
+		}
+	}
+
+	#[test]
+	fn test_decode_into_small_buffer() {
+		let mut output = [0u8; 2];
+		assert_eq!(decode_into("ZiCa", &mut output), Err(FromBase58Error::BufferTooSmall { required: 3 }));
+		assert_eq!(decode_into("111", &mut output), Err(FromBase58Error::BufferTooSmall { required: 3 }));
+		assert_eq!(decode_into("1112", &mut output), Err(FromBase58Error::BufferTooSmall { required: 4 }));
+		assert_eq!(decode_into("ZiC0", &mut output), Err(FromBase58Error::InvalidBase58Character {
+			character: '0',
+			byte_offset: 3,
+			char_index: 3,
+		}));
+
+		let mut output = [0u8; 3];
+		assert_eq!(decode_into("ZiCa", &mut output), Ok(3));
+	}
+
+	#[test]
+	fn test_buffer_too_small_long_input() {
+		// 733 * len overflows, the required size does not
+		let len = usize::MAX / 2;
+		assert_eq!(buffer_too_small(len, 0), FromBase58Error::BufferTooSmall {
+			required: len / 1000 * 733 + len % 1000 * 733 / 1000 + 1,
+		});
+		assert_eq!(buffer_too_small(len, len), FromBase58Error::BufferTooSmall { required: len });
+		assert_eq!(buffer_too_small(2001, 1), FromBase58Error::BufferTooSmall { required: 1466 + 2 });
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_decode_into_every_buffer_size() {
+		// the number spills from whole limbs into the bytes in front of them when the size is not a multiple of 4
+		for len in 0..40 {
+			let input: [u8; 40] = core::array::from_fn(|i| if i == 1 { 0 } else { (i * 97 + len * 13 + 1) as u8 });
+			let encoded = input[..len].to_base58();
+			for size in 0..len + 6 {
+				let mut output = [0u8; 48];
+				match decode_into(&encoded, &mut output[..size]) {
+					Ok(written) => {
+						assert!(size >= len);
+						assert_eq!(&output[..written], &input[..len]);
+					},
+					Err(FromBase58Error::BufferTooSmall { required }) => assert!(size < len && required >= len),
+					Err(err) => panic!("{:?}", err),
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_decode_array() {
+		assert_eq!(decode_array::<0>(""), Ok([]));
+		assert_eq!(decode_array::<3>("ZiCa"), Ok(*b"abc"));
+		assert_eq!(decode_array::<5>("11ZiCa"), Ok(*b"\0\0abc"));
+		assert_eq!(decode_array::<2>("11"), Ok([0, 0]));
+		assert_eq!(decode_array::<32>("11111111111111111111111111111111"), Ok([0u8; 32]));
+	}
+
+	#[test]
+	fn test_decode_array_too_long() {
+		// 58^20 - 1, 15 bytes, though 20 digits may encode 14
+		let long = "zzzzzzzzzzzzzzzzzzzz";
+		assert_eq!(decode_array::<4>(long), Err(FromBase58Error::TooLong { expected: 4, at_least: 14 }));
+		assert_eq!(decode_array::<8>(long), Err(FromBase58Error::UnexpectedLength { expected: 8, actual: 15 }));
+		assert_eq!(decode_array::<12>(long), Err(FromBase58Error::UnexpectedLength { expected: 12, actual: 15 }));
+		// the number of digits tells the length
+		assert_eq!(decode_array::<2>("ZiCa"), Err(FromBase58Error::UnexpectedLength { expected: 2, actual: 3 }));
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_decode_array_round_trip() {
+		let key = [0xffu8; 32];
+		assert_eq!(decode_array::<32>(&key.to_base58()), Ok(key));
+		let key = [0u8; 32];
+		assert_eq!(decode_array::<32>(&key.to_base58()), Ok(key));
+		assert_eq!(<[u8; 32]>::try_from_base58(&key.to_base58()), Ok(key));
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_decode_array_fixed_sizes() {
+		fn check<const N: usize>() {
+			for zeros in 0..=N {
+				let input: [u8; N] = with_zeros(zeros, zeros);
+				let encoded = input.to_base58();
+				assert_eq!(decode_array::<N>(&encoded), Ok(input));
+				assert_eq!(decode_array::<N>(&(String::from("1") + &encoded)),
+					Err(FromBase58Error::UnexpectedLength { expected: N, actual: N + 1 }));
+				if zeros > 0 {
+					assert_eq!(decode_array::<N>(&encoded[1..]),
+						Err(FromBase58Error::UnexpectedLength { expected: N, actual: N - 1 }));
+				}
+				assert_ne!(decode_array::<N>(&(encoded + "z")), Ok(input));
+			}
+		}
+
+		check::<0>();
+		check::<3>();
+		check::<20>();
+		check::<25>();
+		check::<32>();
+		check::<33>();
+		check::<64>();
+		check::<65>();
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_decode_array_invalid_length() {
+		fn unexpected<T>(expected: usize, actual: usize) -> Result<T, FromBase58Error> {
+			Err(FromBase58Error::UnexpectedLength { expected, actual })
+		}
+
+		// short
+		assert_eq!(decode_array::<4>("ZiCa"), unexpected(4, 3));
+		assert_eq!(decode_array::<4>(""), unexpected(4, 0));
+		// long
+		assert_eq!(decode_array::<2>("ZiCa"), unexpected(2, 3));
+		assert_eq!(decode_array::<0>("Z"), unexpected(0, 1));
+		assert_eq!(decode_array::<32>(&[0xffu8; 36].to_base58()), unexpected(32, 36));
+		assert_eq!(decode_array::<32>(&[0x01u8; 40].to_base58()), unexpected(32, 40));
+		// far too long inputs report a lower bound of their length instead of being decoded
+		for long in &[[0xffu8; 40].to_base58(), [0xffu8; 64].to_base58(), "z".repeat(1_000_000)] {
+			let at_least = validate(long).unwrap().min_decoded_len;
+			assert_eq!(decode_array::<32>(long), Err(FromBase58Error::TooLong { expected: 32, at_least }));
+		}
+		// over-padded
+		assert_eq!(decode_array::<3>("1ZiCa"), unexpected(3, 4));
+		assert_eq!(decode_array::<3>("1111ZiCa"), unexpected(3, 7));
+		assert_eq!(decode_array::<2>("111"), unexpected(2, 3));
+		assert_eq!(decode_array::<32>(&(String::from("1") + &[0xffu8; 32].to_base58())), unexpected(32, 33));
+
+		assert_eq!(decode_array::<3>("ZiC0"), Err(FromBase58Error::InvalidBase58Character {
+			character: '0',
+			byte_offset: 3,
+			char_index: 3,
+		}));
+	}
+}
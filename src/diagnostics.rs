@@ -0,0 +1,211 @@
+//! Diagnostics for invalid base58 input
+
+use core::fmt;
+use core::str::CharIndices;
+use Alphabet;
+
+/// Classification of a character which is not a part of the alphabet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvalidKind {
+	/// One of '0', 'O', 'I' and 'l', left out of base58 alphabets because they look like other characters.
+	Confusable,
+	/// Whitespace, including zero-width spaces and joiners.
+	Whitespace,
+	/// Any other character outside of ASCII, e.g. a look-alike letter from another script.
+	NonAscii,
+	/// ASCII punctuation.
+	Punctuation,
+	/// Any other ASCII character, e.g. a control character.
+	Other,
+}
+
+/// A character of the input which is not a part of the alphabet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidCharacter {
+	/// The invalid character.
+	pub character: char,
+	/// Offset of the first byte of the character in the input.
+	pub byte_offset: usize,
+	/// Index of the character among the characters of the input.
+	pub char_index: usize,
+	/// What kind of character it is.
+	pub kind: InvalidKind,
+	/// The alphabet character that was most likely intended, if any.
+	pub suggestion: Option<char>,
+}
+
+impl fmt::Display for InvalidCharacter {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "invalid base58 character {:?} at position {}", self.character, self.char_index)?;
+		match self.suggestion {
+			Some(suggestion) => write!(f, ", did you mean {:?}?", suggestion),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Iterator over every character of an input which is not a part of the alphabet, see [`diagnose`].
+#[derive(Debug, Clone)]
+pub struct Diagnostics<'a> {
+	alphabet: &'a Alphabet,
+	chars: CharIndices<'a>,
+	char_index: usize,
+}
+
+impl<'a> Iterator for Diagnostics<'a> {
+	type Item = InvalidCharacter;
+
+	fn next(&mut self) -> Option<InvalidCharacter> {
+		for (byte_offset, character) in self.chars.by_ref() {
+			let char_index = self.char_index;
+			self.char_index += 1;
+			if self.alphabet.contains(character) {
+				continue;
+			}
+
+			return Some(InvalidCharacter {
+				character,
+				byte_offset,
+				char_index,
+				kind: kind(character),
+				suggestion: suggestion(character, self.alphabet),
+			});
+		}
+
+		None
+	}
+}
+
+/// Returns an iterator over every character of `input` which is not a part of the base58 alphabet,
+/// classifying each one and suggesting the intended character.
+pub fn diagnose(input: &str) -> Diagnostics<'_> {
+	diagnose_with(input, &Alphabet::BITCOIN)
+}
+
+pub(crate) fn diagnose_with<'a>(input: &'a str, alphabet: &'a Alphabet) -> Diagnostics<'a> {
+	Diagnostics {
+		alphabet,
+		chars: input.char_indices(),
+		char_index: 0,
+	}
+}
+
+fn kind(c: char) -> InvalidKind {
+	match c {
+		'0' | 'O' | 'I' | 'l' => InvalidKind::Confusable,
+		c if is_whitespace(c) => InvalidKind::Whitespace,
+		c if !c.is_ascii() => InvalidKind::NonAscii,
+		c if c.is_ascii_punctuation() => InvalidKind::Punctuation,
+		_ => InvalidKind::Other,
+	}
+}
+
+/// Returns whether `c` is whitespace, including the zero-width characters.
+pub(crate) fn is_whitespace(c: char) -> bool {
+	match c {
+		'\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{feff}' => true,
+		c => c.is_whitespace(),
+	}
+}
+
+fn suggestion(c: char, alphabet: &Alphabet) -> Option<char> {
+	let c = homoglyph(c).unwrap_or(c);
+	let candidates: &[char] = match c {
+		'0' | 'O' => &['o'],
+		'I' => &['1', 'i'],
+		'l' => &['1', 'L', 'i'],
+		_ => &[],
+	};
+
+	Some(c).iter().chain(candidates).cloned().find(|x| alphabet.contains(*x))
+}
+
+/// Returns the ASCII character `c` looks like, for full-width forms and Cyrillic and Greek look-alikes.
+pub(crate) fn homoglyph(c: char) -> Option<char> {
+	let ascii = match c {
+		// full-width forms of ASCII characters
+		'\u{ff01}'..='\u{ff5e}' => return char::from_u32(c as u32 - 0xfee0),
+		// Cyrillic
+		'А' => 'A', 'В' => 'B', 'Е' => 'E', 'К' => 'K', 'М' => 'M', 'Н' => 'H', 'О' => 'O', 'Р' => 'P',
+		'С' => 'C', 'Т' => 'T', 'Х' => 'X', 'Ѕ' => 'S', 'І' => 'I', 'Ј' => 'J',
+		'а' => 'a', 'е' => 'e', 'о' => 'o', 'р' => 'p', 'с' => 'c', 'у' => 'y', 'х' => 'x', 'ѕ' => 's',
+		'і' => 'i', 'ј' => 'j',
+		// Greek
+		'Α' => 'A', 'Β' => 'B', 'Ε' => 'E', 'Ζ' => 'Z', 'Η' => 'H', 'Ι' => 'I', 'Κ' => 'K', 'Μ' => 'M',
+		'Ν' => 'N', 'Ο' => 'O', 'Ρ' => 'P', 'Τ' => 'T', 'Υ' => 'Y', 'Χ' => 'X', 'ο' => 'o', 'ν' => 'v',
+		_ => return None,
+	};
+
+	Some(ascii)
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+	use alloc::vec::Vec;
+	use alloc::string::ToString;
+	use super::{diagnose, InvalidCharacter, InvalidKind};
+	use Alphabet;
+
+	fn invalid(character: char, byte_offset: usize, char_index: usize, kind: InvalidKind, suggestion: Option<char>)
+		-> InvalidCharacter {
+		InvalidCharacter { character, byte_offset, char_index, kind, suggestion }
+	}
+
+	#[test]
+	fn test_diagnose_valid() {
+		assert_eq!(diagnose("").count(), 0);
+		assert_eq!(diagnose("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa").count(), 0);
+	}
+
+	#[test]
+	fn test_diagnose_confusables() {
+		let found: Vec<_> = diagnose("0OIl").collect();
+		assert_eq!(found, [
+			invalid('0', 0, 0, InvalidKind::Confusable, Some('o')),
+			invalid('O', 1, 1, InvalidKind::Confusable, Some('o')),
+			invalid('I', 2, 2, InvalidKind::Confusable, Some('1')),
+			invalid('l', 3, 3, InvalidKind::Confusable, Some('1')),
+		]);
+		assert_eq!(found[0].to_string(), "invalid base58 character '0' at position 0, did you mean 'o'?");
+	}
+
+	#[test]
+	fn test_diagnose_reports_every_character() {
+		let found: Vec<_> = diagnose("1A1z P1e\u{200b}P5QG-efi\tАВ１é!\u{7}").collect();
+		assert_eq!(found, [
+			invalid(' ', 4, 4, InvalidKind::Whitespace, None),
+			invalid('\u{200b}', 8, 8, InvalidKind::Whitespace, None),
+			invalid('-', 15, 13, InvalidKind::Punctuation, None),
+			invalid('\t', 19, 17, InvalidKind::Whitespace, None),
+			invalid('А', 20, 18, InvalidKind::NonAscii, Some('A')),
+			invalid('В', 22, 19, InvalidKind::NonAscii, Some('B')),
+			invalid('１', 24, 20, InvalidKind::NonAscii, Some('1')),
+			invalid('é', 27, 21, InvalidKind::NonAscii, None),
+			invalid('!', 29, 22, InvalidKind::Punctuation, None),
+			invalid('\u{7}', 30, 23, InvalidKind::Other, None),
+		]);
+		assert_eq!(found[2].to_string(), "invalid base58 character '-' at position 13");
+	}
+
+	#[test]
+	fn test_diagnose_homoglyph_of_confusable() {
+		// Cyrillic 'О' and full-width '０' look like the excluded '0' and 'O'
+		let found: Vec<_> = diagnose("Оо０").collect();
+		assert_eq!(found, [
+			invalid('О', 0, 0, InvalidKind::NonAscii, Some('o')),
+			invalid('о', 2, 1, InvalidKind::NonAscii, Some('o')),
+			invalid('０', 4, 2, InvalidKind::NonAscii, Some('o')),
+		]);
+	}
+
+	#[test]
+	fn test_diagnose_with_alphabet() {
+		// ripple has no '0' either, but uses 'r' for zero and left out 'l'
+		let found: Vec<_> = Alphabet::RIPPLE.diagnose("r0l").collect();
+		assert_eq!(found, [
+			invalid('0', 1, 1, InvalidKind::Confusable, Some('o')),
+			invalid('l', 2, 2, InvalidKind::Confusable, Some('1')),
+		]);
+	}
+}
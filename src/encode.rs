@@ -0,0 +1,360 @@
+//! Base58 encoding
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::convert::TryInto;
+#[cfg(feature = "alloc")]
+use large;
+use {Alphabet, ToBase58Error};
+
+/// 58^5, the largest power of 58 which fits in a `u32` limb.
+const LIMB_BASE: u64 = 58 * 58 * 58 * 58 * 58;
+
+/// Number of base58 digits in a limb.
+const LIMB_DIGITS: usize = 5;
+
+/// Number of limbs kept on the stack, enough for inputs of up to `STACK_INPUT_LEN` significant bytes.
+/// Longer inputs keep their limbs in the output buffer instead.
+const STACK_LIMBS: usize = 32;
+const STACK_INPUT_LEN: usize = 116;
+
+/// Returns the maximum length of the base58 encoding of `input_len` bytes.
+///
+/// Every byte takes at most log(256) / log(58) ~= 1.37 characters. Saturates at `usize::MAX`.
+pub const fn max_encoded_len(input_len: usize) -> usize {
+	scratch_len(input_len)
+}
+
+/// Number of base58 digits used while encoding `len` bytes.
+const fn scratch_len(len: usize) -> usize {
+	(len / 100).saturating_mul(138).saturating_add(len % 100 * 138 / 100 + 1)
+}
+
+/// Writes `input` as base58 characters into `output`, returning the number of bytes written.
+///
+/// No allocation is made, `output` is used as scratch space and its contents after the returned length
+/// are unspecified. A buffer of [`max_encoded_len`] bytes is always large enough.
+pub fn encode_into(input: &[u8], output: &mut [u8]) -> Result<usize, ToBase58Error> {
+	encode_into_with(input, output, &Alphabet::BITCOIN)
+}
+
+pub(crate) fn encode_into_with(input: &[u8], output: &mut [u8], alphabet: &Alphabet) -> Result<usize, ToBase58Error> {
+	match input.len() {
+		20 => encode_array_with::<20>(input.try_into().unwrap(), output, alphabet),
+		25 => encode_array_with::<25>(input.try_into().unwrap(), output, alphabet),
+		32 => encode_array_with::<32>(input.try_into().unwrap(), output, alphabet),
+		33 => encode_array_with::<33>(input.try_into().unwrap(), output, alphabet),
+		64 => encode_array_with::<64>(input.try_into().unwrap(), output, alphabet),
+		_ => encode_slice_with(input, output, alphabet),
+	}
+}
+
+/// Writes the `N` bytes of `input` as base58 characters into `output`, returning the number of bytes written.
+///
+/// Behaves exactly like [`encode_into`], with the limb arithmetic specialized for the length known at compile
+/// time. [`encode_into`] already takes this path for 20, 25, 32, 33 and 64 byte inputs.
+pub fn encode_array<const N: usize>(input: &[u8; N], output: &mut [u8]) -> Result<usize, ToBase58Error> {
+	encode_array_with(input, output, &Alphabet::BITCOIN)
+}
+
+pub(crate) fn encode_array_with<const N: usize>(
+	input: &[u8; N],
+	output: &mut [u8],
+	alphabet: &Alphabet
+) -> Result<usize, ToBase58Error> {
+	if N > STACK_INPUT_LEN {
+		return encode_slice_with(input, output, alphabet);
+	}
+
+	let zcount = input.iter().take_while(|x| **x == 0).count();
+	let required = zcount + scratch_len(N - zcount);
+	if output.len() < required {
+		return Err(ToBase58Error::BufferTooSmall { required });
+	}
+
+	// a limb holds more than 29 bits of the input, leading zero bytes are converted like any other byte so that
+	// every pass goes over the same number of limbs
+	let mut limbs = [0u32; STACK_LIMBS];
+	let limbs = &mut limbs[..(N * 8).div_ceil(29)];
+	let (head, words) = input.split_at(N % 4);
+	for word in Some(head).into_iter().chain(words.chunks_exact(4)) {
+		let shift = 8 * word.len() as u32;
+		let mut carry = word.iter().fold(0u64, |acc, x| acc << 8 | *x as u64);
+		for limb in limbs.iter_mut() {
+			carry += (*limb as u64) << shift;
+			*limb = (carry % LIMB_BASE) as u32;
+			carry /= LIMB_BASE;
+		}
+	}
+
+	let used = limbs.len() - limbs.iter().rev().take_while(|x| **x == 0).count();
+	let (zeros, buffer) = output.split_at_mut(zcount);
+	let digits = to_digits(buffer, used, |_, i| limbs[i]);
+	Ok(finish(zeros, &mut buffer[..digits], alphabet))
+}
+
+fn encode_slice_with(input: &[u8], output: &mut [u8], alphabet: &Alphabet) -> Result<usize, ToBase58Error> {
+	let zcount = input.iter().take_while(|x| **x == 0).count();
+	let input = &input[zcount..];
+	let size = scratch_len(input.len());
+	let required = zcount + size;
+	if output.len() < required {
+		return Err(ToBase58Error::BufferTooSmall { required });
+	}
+
+	let (zeros, buffer) = output.split_at_mut(zcount);
+	let buffer = &mut buffer[..size];
+	let digits = if input.len() <= STACK_INPUT_LEN {
+		let mut limbs = [0u32; STACK_LIMBS];
+		let used = to_limbs(input, &mut limbs[..]);
+		to_digits(buffer, used, |_, i| limbs[i])
+	} else {
+		// the limbs take 4 bytes per 5 digits, so they fit in the tail of the buffer and are read before
+		// the digits written from its front reach them
+		let used = to_limbs(input, &mut TailLimbs(buffer));
+		to_digits(buffer, used, tail_limb)
+	};
+
+	Ok(finish(zeros, &mut buffer[..digits], alphabet))
+}
+
+/// Maps the leading zeros and the digits after them to the alphabet, returning the encoded length.
+pub(crate) fn finish(zeros: &mut [u8], digits: &mut [u8], alphabet: &Alphabet) -> usize {
+	for c in zeros.iter_mut() {
+		*c = alphabet.char(0);
+	}
+
+	for c in digits.iter_mut() {
+		*c = alphabet.char(*c);
+	}
+
+	zeros.len() + digits.len()
+}
+
+/// Storage of base 58^5 limbs, indexed from the least significant one.
+trait Limbs {
+	fn limb(&self, i: usize) -> u32;
+	fn set_limb(&mut self, i: usize, limb: u32);
+}
+
+impl Limbs for [u32] {
+	fn limb(&self, i: usize) -> u32 {
+		self[i]
+	}
+
+	fn set_limb(&mut self, i: usize, limb: u32) {
+		self[i] = limb;
+	}
+}
+
+/// Limbs kept at the end of a byte buffer, the least significant one last.
+struct TailLimbs<'a>(&'a mut [u8]);
+
+impl<'a> Limbs for TailLimbs<'a> {
+	fn limb(&self, i: usize) -> u32 {
+		tail_limb(self.0, i)
+	}
+
+	fn set_limb(&mut self, i: usize, limb: u32) {
+		let end = self.0.len() - 4 * i;
+		self.0[end - 4..end].copy_from_slice(&limb.to_ne_bytes());
+	}
+}
+
+fn tail_limb(buffer: &[u8], i: usize) -> u32 {
+	let end = buffer.len() - 4 * i;
+	u32::from_ne_bytes([buffer[end - 4], buffer[end - 3], buffer[end - 2], buffer[end - 1]])
+}
+
+/// Converts the big-endian number `input` to base 58^5 limbs, returning the number of limbs used.
+///
+/// The input is consumed four bytes at a time, the first word taking the bytes which don't fill a whole one.
+fn to_limbs<L: Limbs + ?Sized>(input: &[u8], limbs: &mut L) -> usize {
+	let (head, words) = input.split_at(input.len() % 4);
+	let mut used = 0;
+
+	for word in Some(head).into_iter().chain(words.chunks(4)) {
+		let shift = 8 * word.len() as u32;
+		let mut carry = word.iter().fold(0u64, |acc, x| acc << 8 | *x as u64);
+		for i in 0..used {
+			// limb < 2^30, so this stays below 2^62 + carry
+			carry += (limbs.limb(i) as u64) << shift;
+			limbs.set_limb(i, (carry % LIMB_BASE) as u32);
+			carry /= LIMB_BASE;
+		}
+
+		while carry != 0 {
+			limbs.set_limb(used, (carry % LIMB_BASE) as u32);
+			carry /= LIMB_BASE;
+			used += 1;
+		}
+	}
+
+	used
+}
+
+/// Writes the base58 digits of `used` limbs, most significant first and without leading zeros, to the
+/// front of `buffer`, returning the number of digits. `limb` returns a limb, it is called before the
+/// digits of that limb are written.
+pub(crate) fn to_digits<F: Fn(&[u8], usize) -> u32>(buffer: &mut [u8], used: usize, limb: F) -> usize {
+	if used == 0 {
+		return 0;
+	}
+
+	let top = limb(buffer, used - 1);
+	let mut len = 0;
+	let mut x = top;
+	while x != 0 {
+		len += 1;
+		x /= 58;
+	}
+	write_digits(top, &mut buffer[..len]);
+
+	for i in (0..used - 1).rev() {
+		let limb = limb(buffer, i);
+		write_digits(limb, &mut buffer[len..len + LIMB_DIGITS]);
+		len += LIMB_DIGITS;
+	}
+
+	len
+}
+
+fn write_digits(mut limb: u32, digits: &mut [u8]) {
+	for digit in digits.iter_mut().rev() {
+		*digit = (limb % 58) as u8;
+		limb /= 58;
+	}
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn encode(input: &[u8], alphabet: &Alphabet) -> String {
+	if input.len() >= large::THRESHOLD {
+		return large::encode(input, alphabet);
+	}
+
+	let mut buffer = vec![0u8; max_encoded_len(input.len())];
+	let len = encode_into_with(input, &mut buffer, alphabet).expect("buffer of max_encoded_len is large enough");
+	buffer[..len].iter().map(|c| *c as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	#[cfg(feature = "alloc")]
+	use alloc::vec::Vec;
+	use super::{encode_into, max_encoded_len};
+	#[cfg(feature = "alloc")]
+	use super::{encode_array, STACK_INPUT_LEN};
+	#[cfg(feature = "alloc")]
+	use test_util::{with_zeros, xorshift};
+	#[cfg(feature = "alloc")]
+	use {Alphabet, ToBase58};
+	use ToBase58Error;
+
+	/// One digit at a time, as the encoder used to work.
+	#[cfg(feature = "alloc")]
+	fn reference(input: &[u8]) -> Vec<u8> {
+		let zcount = input.iter().take_while(|x| **x == 0).count();
+		let mut digits: Vec<u8> = Vec::new();
+		for x in &input[zcount..] {
+			let mut carry = *x as u32;
+			for digit in digits.iter_mut() {
+				carry += (*digit as u32) << 8;
+				*digit = (carry % 58) as u8;
+				carry /= 58;
+			}
+			while carry != 0 {
+				digits.push((carry % 58) as u8);
+				carry /= 58;
+			}
+		}
+
+		digits.extend((0..zcount).map(|_| 0));
+		digits.iter().rev().map(|x| Alphabet::BITCOIN.as_str().as_bytes()[*x as usize]).collect()
+	}
+
+	#[test]
+	fn test_encode_into() {
+		let mut output = [0xffu8; 64];
+		assert_eq!(encode_into(b"", &mut output), Ok(0));
+		assert_eq!(encode_into(b"abc", &mut output), Ok(4));
+		assert_eq!(&output[..4], b"ZiCa");
+		assert_eq!(encode_into(b"\0\0abc", &mut output), Ok(6));
+		assert_eq!(&output[..6], b"11ZiCa");
+		assert_eq!(encode_into(b"\0\0\0", &mut output), Ok(3));
+		assert_eq!(&output[..3], b"111");
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_encode_into_matches_to_base58() {
+		let mut output = [0u8; 512];
+		for len in 0..256 {
+			let input: [u8; 256] = with_zeros(len / 8, len);
+			let written = encode_into(&input[..len], &mut output).unwrap();
+			assert!(written <= max_encoded_len(len));
+			assert_eq!(&output[..written], input[..len].to_base58().as_bytes());
+		}
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_encode_into_matches_reference() {
+		let mut output = [0u8; 1024];
+		let mut seed = 0x2545f491u32;
+		for len in (0..STACK_INPUT_LEN + 16).chain(500..520) {
+			for fill in &[0x00, 0xff, 0x01] {
+				let mut input = [*fill; 520];
+				for x in input[len / 3..len].iter_mut() {
+					*x = xorshift(&mut seed) as u8;
+				}
+
+				let written = encode_into(&input[..len], &mut output[..max_encoded_len(len)]).unwrap();
+				assert_eq!(&output[..written], &reference(&input[..len])[..], "len {}", len);
+			}
+		}
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_encode_array() {
+		fn check<const N: usize>() {
+			let mut output = [0u8; 256];
+			for zeros in 0..=N {
+				let input: [u8; N] = with_zeros(zeros, zeros);
+				let written = encode_array(&input, &mut output).unwrap();
+				assert_eq!(&output[..written], &reference(&input)[..], "N {} zeros {}", N, zeros);
+				assert_eq!(encode_array(&[0xff; N], &mut output[..1]), encode_into(&[0xff; N], &mut output[..1]));
+			}
+		}
+
+		check::<0>();
+		check::<1>();
+		check::<7>();
+		check::<20>();
+		check::<25>();
+		check::<32>();
+		check::<33>();
+		check::<64>();
+		check::<116>();
+		check::<117>();
+	}
+
+	#[test]
+	fn test_encode_into_small_buffer() {
+		let mut output = [0u8; 3];
+		assert_eq!(encode_into(b"abc", &mut output), Err(ToBase58Error::BufferTooSmall { required: 5 }));
+		assert_eq!(encode_into(b"\0\0\0\0", &mut output), Err(ToBase58Error::BufferTooSmall { required: 5 }));
+
+		let mut output = [0u8; 5];
+		assert_eq!(encode_into(b"abc", &mut output), Ok(4));
+	}
+
+	#[test]
+	fn test_max_encoded_len() {
+		assert_eq!(max_encoded_len(0), 1);
+		assert_eq!(max_encoded_len(32), 45);
+		let max = isize::MAX as usize;
+		assert_eq!(max_encoded_len(max), max / 100 * 138 + 1 + max % 100 * 138 / 100);
+		assert_eq!(max_encoded_len(usize::MAX), usize::MAX);
+	}
+}
@@ -0,0 +1,114 @@
+//! Minimal Keccak-256 implementation, used for Monero address checksums
+//!
+//! This is the original Keccak submission as used by Monero, which pads differently from the
+//! standardized SHA3-256.
+
+const ROUND_CONSTANTS: [u64; 24] = [
+	0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+	0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+	0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+	0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+	0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+	0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+/// Rotation of every lane visited by the combined rho and pi steps, in visiting order.
+const ROTATIONS: [u32; 24] = [
+	1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+/// Lanes visited by the combined rho and pi steps, starting from lane 1.
+const LANES: [usize; 24] = [10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1];
+
+/// Number of bytes absorbed per permutation, 1600 bits of state minus twice the 256 bit output.
+const RATE: usize = 136;
+
+fn keccak_f(state: &mut [u64; 25]) {
+	for round_constant in ROUND_CONSTANTS.iter() {
+		// theta
+		let mut columns = [0u64; 5];
+		for (i, lane) in state.iter().enumerate() {
+			columns[i % 5] ^= lane;
+		}
+		for (i, lane) in state.iter_mut().enumerate() {
+			*lane ^= columns[(i + 4) % 5] ^ columns[(i + 1) % 5].rotate_left(1);
+		}
+
+		// rho and pi
+		let mut last = state[1];
+		for (lane, rotation) in LANES.iter().zip(ROTATIONS.iter()) {
+			let next = state[*lane];
+			state[*lane] = last.rotate_left(*rotation);
+			last = next;
+		}
+
+		// chi
+		for row in state.chunks_mut(5) {
+			let copy = [row[0], row[1], row[2], row[3], row[4]];
+			for (i, lane) in row.iter_mut().enumerate() {
+				*lane = copy[i] ^ (!copy[(i + 1) % 5] & copy[(i + 2) % 5]);
+			}
+		}
+
+		// iota
+		state[0] ^= round_constant;
+	}
+}
+
+fn absorb(state: &mut [u64; 25], block: &[u8; RATE]) {
+	for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+		*lane ^= u64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]);
+	}
+	keccak_f(state);
+}
+
+/// Computes the Keccak-256 digest of `data`.
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+	let mut state = [0u64; 25];
+	let mut blocks = data.chunks_exact(RATE);
+	for block in blocks.by_ref() {
+		let mut full = [0u8; RATE];
+		full.copy_from_slice(block);
+		absorb(&mut state, &full);
+	}
+
+	let rest = blocks.remainder();
+	let mut last = [0u8; RATE];
+	last[..rest.len()].copy_from_slice(rest);
+	last[rest.len()] ^= 0x01;
+	last[RATE - 1] ^= 0x80;
+	absorb(&mut state, &last);
+
+	let mut result = [0u8; 32];
+	for (chunk, lane) in result.chunks_mut(8).zip(state.iter()) {
+		chunk.copy_from_slice(&lane.to_le_bytes());
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::keccak256;
+	use test_util::unhex;
+
+	#[test]
+	fn test_keccak256() {
+		assert_eq!(keccak256(b""), unhex("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"));
+		assert_eq!(keccak256(b"abc"), unhex("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"));
+	}
+
+	#[test]
+	fn test_keccak256_blocks() {
+		// around the rate of 136 bytes: padding in one block, a whole block and padding in a second one
+		let vectors = [
+			(135, "34367dc248bbd832f4e3e69dfaac2f92638bd0bbd18f2912ba4ef454919cf446"),
+			(136, "a6c4d403279fe3e0af03729caada8374b5ca54d8065329a3ebcaeb4b60aa386e"),
+			(137, "d869f639c7046b4929fc92a4d988a8b22c55fbadb802c0c66ebcd484f1915f39"),
+			(200, "96ea54061def936c4be90b518992fdc6f12f535068a256229aca54267b4d084d"),
+			(272, "cf7fcd4f705ee749930d19ca84561a9bf62516bd90a471545fa2f49fdc7e63c8"),
+		];
+		for (len, hash) in vectors {
+			assert_eq!(keccak256(&[b'a'; 272][..len]), unhex(hash), "{}", len);
+		}
+	}
+}
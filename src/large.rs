@@ -0,0 +1,465 @@
+//! Subquadratic conversion for large inputs
+//!
+//! The digits are split in halves, both halves are converted recursively and joined as
+//! `high * base^len(low) + low` with multiplication in the target radix, so no division is needed. Products of
+//! long operands are computed by number theoretic transforms, so the conversion takes O(n log^2 n) time.
+//! Numbers are little-endian vectors of `u32` limbs of radix 2^32 for bytes and 58^5 for base58 digits.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use decode::invalid_character;
+use encode;
+use {Alphabet, FromBase58Error};
+
+/// Input length from which [`ToBase58`](::ToBase58) and [`FromBase58`](::FromBase58) switch to the
+/// subquadratic conversion.
+pub(crate) const THRESHOLD: usize = 1024;
+
+const BYTES: u64 = 1 << 32;
+const DIGITS: u64 = 58 * 58 * 58 * 58 * 58;
+
+/// Number of digits converted directly, without splitting.
+const LEAF_LEN: usize = 128;
+
+/// Operands shorter than this many limbs are multiplied by the schoolbook method.
+const KARATSUBA_LEN: usize = 32;
+
+/// Operands at least this many limbs long are multiplied by number theoretic transforms.
+const NTT_LEN: usize = 512;
+
+/// Primes `c * 2^k + 1` with primitive root 3, the transforms are computed modulo each of them and the
+/// coefficients recovered by the chinese remainder theorem.
+const P0: u64 = 119 << 23 | 1;
+const P1: u64 = 5 << 25 | 1;
+const P2: u64 = 7 << 26 | 1;
+
+/// Longest transform, limited by the `2^23` of `P0`. The coefficients of a product of this length are below
+/// `2^22 * 2^64`, which is less than `P0 * P1 * P2` and so recovered exactly.
+const NTT_MAX_LEN: usize = 1 << 23;
+
+/// Converts the big-endian `input` to base58 characters.
+pub(crate) fn encode(input: &[u8], alphabet: &Alphabet) -> String {
+	let zcount = input.iter().take_while(|x| **x == 0).count();
+	let limbs = convert::<DIGITS>(&input[zcount..], 256);
+
+	let mut buffer = vec![0u8; zcount + limbs.len() * 5];
+	let (zeros, digits) = buffer.split_at_mut(zcount);
+	let len = encode::to_digits(digits, limbs.len(), |_, i| limbs[i]);
+	let len = encode::finish(zeros, &mut digits[..len], alphabet);
+	buffer.truncate(len);
+	buffer.into_iter().map(|c| c as char).collect()
+}
+
+/// Converts the base58 string `input` to bytes.
+pub(crate) fn decode(input: &str, alphabet: &Alphabet) -> Result<Vec<u8>, FromBase58Error> {
+	let mut digits = Vec::with_capacity(input.len());
+	for (i, c) in input.bytes().enumerate() {
+		digits.push(alphabet.digit(c).ok_or_else(|| invalid_character(input, i))?);
+	}
+
+	let zcount = digits.iter().take_while(|x| **x == 0).count();
+	let limbs = convert::<BYTES>(&digits[zcount..], 58);
+
+	let mut output = vec![0u8; zcount];
+	let number = limbs.iter().rev().flat_map(|limb| limb.to_be_bytes());
+	output.extend(number.skip_while(|x| *x == 0));
+	Ok(output)
+}
+
+/// Converts the big-endian digits of base `base` to limbs of radix `R`, without trailing zero limbs.
+fn convert<const R: u64>(digits: &[u8], base: u32) -> Vec<u32> {
+	// `powers[i]` is base^(LEAF_LEN << i)
+	let mut powers: Vec<Vec<u32>> = Vec::new();
+	while LEAF_LEN << powers.len() < digits.len() {
+		let power = match powers.last() {
+			Some(last) => mul::<R>(last, last),
+			None => {
+				let mut one = vec![0u8; LEAF_LEN + 1];
+				one[0] = 1;
+				leaf::<R>(&one, base)
+			},
+		};
+		powers.push(trimmed(power));
+	}
+
+	split::<R>(digits, base, &powers)
+}
+
+/// Converts `digits`, at most `LEAF_LEN << powers.len()` of them.
+fn split<const R: u64>(digits: &[u8], base: u32, powers: &[Vec<u32>]) -> Vec<u32> {
+	let (power, powers) = match powers.split_last() {
+		Some(x) => x,
+		None => return trimmed(leaf::<R>(digits, base)),
+	};
+
+	let low_len = LEAF_LEN << powers.len();
+	if digits.len() <= low_len {
+		return split::<R>(digits, base, powers);
+	}
+
+	let (high, low) = digits.split_at(digits.len() - low_len);
+	let mut number = mul::<R>(&split::<R>(high, base, powers), power);
+	add_at::<R>(&mut number, &split::<R>(low, base, powers), 0);
+	trimmed(number)
+}
+
+/// Converts `digits` by the quadratic method, as many digits at a time as fit in a `u32`.
+fn leaf<const R: u64>(digits: &[u8], base: u32) -> Vec<u32> {
+	let mut group = 1;
+	while (base as u64).pow(group as u32 + 1) <= 1 << 32 {
+		group += 1;
+	}
+
+	let mut number = Vec::new();
+	for chunk in digits.chunks(group) {
+		let mut carry = chunk.iter().fold(0u64, |acc, x| acc * base as u64 + *x as u64);
+		let factor = (base as u64).pow(chunk.len() as u32);
+		for limb in number.iter_mut() {
+			carry += *limb as u64 * factor;
+			*limb = (carry % R) as u32;
+			carry /= R;
+		}
+
+		while carry != 0 {
+			number.push((carry % R) as u32);
+			carry /= R;
+		}
+	}
+
+	number
+}
+
+fn mul<const R: u64>(a: &[u32], b: &[u32]) -> Vec<u32> {
+	let (a, b) = if a.len() < b.len() { (b, a) } else { (a, b) };
+	if b.len() < KARATSUBA_LEN {
+		return schoolbook::<R>(a, b);
+	}
+	if b.len() >= NTT_LEN && a.len() + b.len() <= NTT_MAX_LEN {
+		return ntt_mul::<R>(a, b);
+	}
+
+	let mut product = vec![0u32; a.len() + b.len()];
+	if a.len() >= 2 * b.len() {
+		// too unbalanced to split both, multiply by pieces of `a` as long as `b`
+		for (i, piece) in a.chunks(b.len()).enumerate() {
+			add_at::<R>(&mut product, &mul::<R>(piece, b), i * b.len());
+		}
+		return product;
+	}
+
+	// a = a1 * R^m + a0, b = b1 * R^m + b0 and a * b = z2 * R^2m + z1 * R^m + z0
+	let m = a.len() / 2;
+	let (a0, a1) = a.split_at(m);
+	let (b0, b1) = b.split_at(m);
+	let z0 = mul::<R>(a0, b0);
+	let z2 = mul::<R>(a1, b1);
+	let mut z1 = mul::<R>(&add::<R>(a0, a1), &add::<R>(b0, b1));
+	sub_assign::<R>(&mut z1, &z0);
+	sub_assign::<R>(&mut z1, &z2);
+
+	add_at::<R>(&mut product, &z0, 0);
+	add_at::<R>(&mut product, &z1, m);
+	add_at::<R>(&mut product, &z2, 2 * m);
+	product
+}
+
+fn schoolbook<const R: u64>(a: &[u32], b: &[u32]) -> Vec<u32> {
+	let mut product = vec![0u32; a.len() + b.len()];
+	for (i, x) in a.iter().enumerate() {
+		let mut carry = 0;
+		for (j, y) in b.iter().enumerate() {
+			// at most (R - 1)^2 + 2 * (R - 1) = R^2 - 1
+			carry += product[i + j] as u64 + *x as u64 * *y as u64;
+			product[i + j] = (carry % R) as u32;
+			carry /= R;
+		}
+		product[i + b.len()] = carry as u32;
+	}
+
+	product
+}
+
+/// Multiplies by convolving the limbs modulo three primes, then carrying the recovered coefficients.
+fn ntt_mul<const R: u64>(a: &[u32], b: &[u32]) -> Vec<u32> {
+	let len = (a.len() + b.len()).next_power_of_two();
+	let c0 = convolve::<P0>(a, b, len);
+	let c1 = convolve::<P1>(a, b, len);
+	let c2 = convolve::<P2>(a, b, len);
+
+	// Garner's algorithm, the coefficient is x0 + x1 * P0 + x2 * P0 * P1
+	let inv_p0_p1 = pow_mod::<P1>(P0 % P1, P1 - 2);
+	let inv_p0_p2 = pow_mod::<P2>(P0 % P2, P2 - 2);
+	let inv_p1_p2 = pow_mod::<P2>(P1 % P2, P2 - 2);
+
+	let mut product = vec![0u32; a.len() + b.len()];
+	let mut carry = 0u128;
+	for (i, limb) in product.iter_mut().enumerate() {
+		let x0 = c0[i] as u64;
+		let x1 = (c1[i] as u64 + P1 - x0 % P1) * inv_p0_p1 % P1;
+		let x2 = ((c2[i] as u64 + P2 - x0 % P2) * inv_p0_p2 % P2 + P2 - x1 % P2) * inv_p1_p2 % P2;
+		carry += x0 as u128 + (x1 * P0) as u128 + (x2 * P0) as u128 * P1 as u128;
+
+		// R < 2^32, so dividing 32 bits at a time keeps every step in a u64
+		let high = (carry >> 64) as u64;
+		let middle = (high % R) << 32 | (carry >> 32) as u32 as u64;
+		let low = (middle % R) << 32 | carry as u32 as u64;
+		*limb = (low % R) as u32;
+		carry = ((high / R) as u128) << 64 | ((middle / R) as u128) << 32 | (low / R) as u128;
+	}
+
+	product
+}
+
+/// Returns the cyclic convolution of `a` and `b` of length `len` modulo `P`.
+fn convolve<const P: u64>(a: &[u32], b: &[u32], len: usize) -> Vec<u32> {
+	let forward = roots::<P>(len, false);
+	let mut fa = transform::<P>(a, len, &forward);
+	let fb = transform::<P>(b, len, &forward);
+	for (x, y) in fa.iter_mut().zip(&fb) {
+		*x = mont_mul::<P>(*x, *y);
+	}
+
+	inverse_ntt::<P>(&mut fa, &roots::<P>(len, true));
+	// undoes the scaling by `len` of the transforms and the 2^-32 of the pointwise products
+	let scale = (pow_mod::<P>(len as u64, P - 2) << 32) % P;
+	let scale = ((scale << 32) % P) as u32;
+	for x in fa.iter_mut() {
+		*x = mont_mul::<P>(*x, scale);
+	}
+	fa
+}
+
+/// Returns the transform modulo `P` of `limbs` padded to `len` values.
+fn transform<const P: u64>(limbs: &[u32], len: usize, roots: &[u32]) -> Vec<u32> {
+	let mut values = Vec::with_capacity(len);
+	values.extend(limbs.iter().map(|x| (*x as u64 % P) as u32));
+	values.resize(len, 0);
+	ntt::<P>(&mut values, roots);
+	values
+}
+
+struct Montgomery<const P: u64>;
+
+impl<const P: u64> Montgomery<P> {
+	/// -P^-1 mod 2^32, by Newton's iteration.
+	const NEG_INV: u32 = {
+		let mut inv = P as u32;
+		let mut i = 0;
+		while i < 4 {
+			inv = inv.wrapping_mul(2u32.wrapping_sub((P as u32).wrapping_mul(inv)));
+			i += 1;
+		}
+		inv.wrapping_neg()
+	};
+}
+
+/// Returns `a * b * 2^-32 mod P`, the Montgomery product.
+#[inline(always)]
+fn mont_mul<const P: u64>(a: u32, b: u32) -> u32 {
+	let t = a as u64 * b as u64;
+	let m = (t as u32).wrapping_mul(Montgomery::<P>::NEG_INV);
+	let u = ((t + m as u64 * P) >> 32) as u32;
+	if u as u64 >= P { u - P as u32 } else { u }
+}
+
+/// Returns the powers of primitive roots of unity modulo `P`, or of their inverses, for every stage of a transform
+/// of `len` values: the `half` powers of the `2 * half`th root start at index `half`. They are in Montgomery form,
+/// so multiplying by them keeps the values in the normal form.
+fn roots<const P: u64>(len: usize, inverse: bool) -> Vec<u32> {
+	let root = pow_mod::<P>(3, (P - 1) / len as u64);
+	let root = if inverse { pow_mod::<P>(root, P - 2) } else { root };
+	let mut roots = vec![0; len];
+	let (mut w, root) = (((1u64 << 32) % P) as u32, ((root << 32) % P) as u32);
+	for x in roots[len / 2..].iter_mut() {
+		*x = w;
+		w = mont_mul::<P>(w, root);
+	}
+	// the `half`th root is the square of the `2 * half`th one
+	for i in (1..len / 2).rev() {
+		roots[i] = roots[2 * i];
+	}
+	roots
+}
+
+/// In-place decimation in frequency transform modulo `P`, leaving the result in bit-reversed order.
+fn ntt<const P: u64>(values: &mut [u32], roots: &[u32]) {
+	let p = P as u32;
+	let mut half = values.len() / 2;
+	while half >= 1 {
+		let roots = &roots[half..2 * half];
+		for chunk in values.chunks_exact_mut(2 * half) {
+			let (low, high) = chunk.split_at_mut(half);
+			for ((x, y), w) in low.iter_mut().zip(high.iter_mut()).zip(roots) {
+				let (a, b) = (*x, *y);
+				*x = if a + b >= p { a + b - p } else { a + b };
+				*y = mont_mul::<P>(if a >= b { a - b } else { a + p - b }, *w);
+			}
+		}
+		half >>= 1;
+	}
+}
+
+/// In-place decimation in time transform modulo `P` of values in bit-reversed order, without the scaling by
+/// `1 / len`, the inverse of [`ntt`] given the inverse roots.
+fn inverse_ntt<const P: u64>(values: &mut [u32], roots: &[u32]) {
+	let p = P as u32;
+	let mut half = 1;
+	while half < values.len() {
+		let roots = &roots[half..2 * half];
+		for chunk in values.chunks_exact_mut(2 * half) {
+			let (low, high) = chunk.split_at_mut(half);
+			for ((x, y), w) in low.iter_mut().zip(high.iter_mut()).zip(roots) {
+				let (a, t) = (*x, mont_mul::<P>(*y, *w));
+				*x = if a + t >= p { a + t - p } else { a + t };
+				*y = if a >= t { a - t } else { a + p - t };
+			}
+		}
+		half <<= 1;
+	}
+}
+
+fn pow_mod<const P: u64>(mut base: u64, mut exp: u64) -> u64 {
+	let mut result = 1;
+	while exp != 0 {
+		if exp & 1 == 1 {
+			result = result * base % P;
+		}
+		base = base * base % P;
+		exp >>= 1;
+	}
+	result
+}
+
+fn add<const R: u64>(a: &[u32], b: &[u32]) -> Vec<u32> {
+	let mut sum = a.to_vec();
+	sum.resize(a.len().max(b.len()) + 1, 0);
+	add_at::<R>(&mut sum, b, 0);
+	sum
+}
+
+/// Adds `b * R^offset` to `a`, which must be long enough for the sum.
+fn add_at<const R: u64>(a: &mut [u32], b: &[u32], offset: usize) {
+	let b = &b[..b.len() - b.iter().rev().take_while(|x| **x == 0).count()];
+	let mut carry = 0;
+	for (i, x) in b.iter().enumerate() {
+		carry += a[offset + i] as u64 + *x as u64;
+		a[offset + i] = (carry % R) as u32;
+		carry /= R;
+	}
+
+	let mut i = offset + b.len();
+	while carry != 0 {
+		carry += a[i] as u64;
+		a[i] = (carry % R) as u32;
+		carry /= R;
+		i += 1;
+	}
+}
+
+/// Subtracts `b` from `a`, which must not be smaller.
+fn sub_assign<const R: u64>(a: &mut [u32], b: &[u32]) {
+	let mut borrow = 0;
+	for (i, x) in a.iter_mut().enumerate() {
+		if i >= b.len() && borrow == 0 {
+			break;
+		}
+
+		let y = b.get(i).map_or(0, |y| *y as u64) + borrow;
+		borrow = (y > *x as u64) as u64;
+		*x = (*x as u64 + borrow * R - y) as u32;
+	}
+}
+
+fn trimmed(mut number: Vec<u32>) -> Vec<u32> {
+	while number.last() == Some(&0) {
+		number.pop();
+	}
+	number
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::String;
+	use alloc::vec::Vec;
+	use super::{encode, decode, mul, ntt_mul, schoolbook, DIGITS, BYTES, LEAF_LEN};
+	use test_util::xorshift;
+	use {Alphabet, FromBase58Error, max_encoded_len};
+
+	#[test]
+	fn test_karatsuba_matches_schoolbook() {
+		let mut seed = 0x1234567;
+		for &(a_len, b_len) in &[(32, 32), (33, 40), (64, 63), (100, 50), (200, 33), (129, 128)] {
+			let a: Vec<u32> = (0..a_len).map(|_| xorshift(&mut seed)).collect();
+			let b: Vec<u32> = (0..b_len).map(|_| xorshift(&mut seed)).collect();
+			assert_eq!(mul::<BYTES>(&a, &b), schoolbook::<BYTES>(&a, &b));
+
+			let a: Vec<u32> = a.iter().map(|x| (*x as u64 % DIGITS) as u32).collect();
+			let b: Vec<u32> = b.iter().map(|x| (*x as u64 % DIGITS) as u32).collect();
+			assert_eq!(mul::<DIGITS>(&a, &b), schoolbook::<DIGITS>(&a, &b));
+		}
+	}
+
+	#[test]
+	fn test_ntt_matches_schoolbook() {
+		let mut seed = 0x7654321;
+		for &(a_len, b_len) in &[(512, 512), (600, 1500), (2048, 2047), (4096, 512)] {
+			let a: Vec<u32> = (0..a_len).map(|_| xorshift(&mut seed)).collect();
+			let b: Vec<u32> = (0..b_len).map(|_| xorshift(&mut seed)).collect();
+			assert_eq!(ntt_mul::<BYTES>(&a, &b), schoolbook::<BYTES>(&a, &b));
+
+			let a: Vec<u32> = a.iter().map(|x| (*x as u64 % DIGITS) as u32).collect();
+			let b: Vec<u32> = b.iter().map(|x| (*x as u64 % DIGITS) as u32).collect();
+			assert_eq!(ntt_mul::<DIGITS>(&a, &b), schoolbook::<DIGITS>(&a, &b));
+		}
+
+		// the largest coefficients, every limb R - 1
+		let a = vec![u32::MAX; 3000];
+		assert_eq!(ntt_mul::<BYTES>(&a, &a), schoolbook::<BYTES>(&a, &a));
+		let a = vec![DIGITS as u32 - 1; 3000];
+		assert_eq!(ntt_mul::<DIGITS>(&a, &a), schoolbook::<DIGITS>(&a, &a));
+	}
+
+	#[test]
+	#[ignore = "takes seconds in debug builds, run with `cargo test --release -- --ignored`"]
+	fn test_round_trip_megabyte() {
+		// too long to check against the quadratic conversion, which takes minutes, the products are checked above
+		let mut seed = 0x2545f491;
+		let input: Vec<u8> = (0..1 << 20).map(|_| xorshift(&mut seed) as u8).collect();
+		let encoded = encode(&input, &Alphabet::BITCOIN);
+		// 8 * 2^20 / log2(58) = 1431997.05 digits
+		assert_eq!(encoded.len(), 1_431_997);
+		assert_eq!(decode(&encoded, &Alphabet::BITCOIN).unwrap(), input);
+	}
+
+	#[test]
+	fn test_matches_quadratic() {
+		let mut seed = 0x9e3779b9;
+		for &len in &[0, 1, LEAF_LEN - 1, LEAF_LEN, LEAF_LEN + 1, 2 * LEAF_LEN + 1, 1000, 5000] {
+			for &zeros in &[0, 3] {
+				let mut input = vec![0u8; zeros];
+				input.extend((0..len).map(|_| xorshift(&mut seed) as u8));
+
+				let mut buffer = vec![0u8; max_encoded_len(input.len())];
+				let written = Alphabet::BITCOIN.encode_into(&input, &mut buffer).unwrap();
+				let expected = String::from_utf8(buffer[..written].to_vec()).unwrap();
+				let encoded = encode(&input, &Alphabet::BITCOIN);
+				assert_eq!(encoded, expected, "len {}", len);
+
+				let mut buffer = vec![0u8; input.len()];
+				let written = Alphabet::BITCOIN.decode_into(&encoded, &mut buffer).unwrap();
+				assert_eq!(decode(&encoded, &Alphabet::BITCOIN).unwrap(), &buffer[..written]);
+				assert_eq!(&buffer[..written], &input[..]);
+			}
+		}
+	}
+
+	#[test]
+	fn test_decode_invalid_character() {
+		let input = String::from("1") + &"z".repeat(300) + "0";
+		assert_eq!(decode(&input, &Alphabet::BITCOIN), Err(FromBase58Error::InvalidBase58Character {
+			character: '0',
+			byte_offset: 301,
+			char_index: 301,
+		}));
+	}
+}
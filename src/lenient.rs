@@ -0,0 +1,187 @@
+//! Lenient decoding of base58 strings copied from emails, documents and scanners
+
+use alloc::vec::Vec;
+use alloc::string::String;
+use diagnostics::{homoglyph, is_whitespace};
+use decode::{decode, invalid_character};
+use {Alphabet, FromBase58Error};
+
+/// A change made to the input before decoding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+	/// A whitespace or separator character was removed.
+	Removed {
+		/// The removed character.
+		character: char,
+		/// Offset of the first byte of the character in the input.
+		byte_offset: usize,
+		/// Index of the character among the characters of the input.
+		char_index: usize,
+	},
+	/// A look-alike character was replaced by the alphabet character it stands for.
+	Replaced {
+		/// The character found in the input.
+		from: char,
+		/// The alphabet character it was replaced with.
+		to: char,
+		/// Offset of the first byte of the character in the input.
+		byte_offset: usize,
+		/// Index of the character among the characters of the input.
+		char_index: usize,
+	},
+}
+
+/// Bytes decoded by the lenient decoder, with every change made to the input to decode them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Normalized {
+	/// The decoded bytes.
+	pub bytes: Vec<u8>,
+	/// Changes made to the input, in input order.
+	pub normalizations: Vec<Normalization>,
+}
+
+/// Opt-in lenient decoder, which strips whitespace and separators and replaces Unicode look-alikes
+/// of alphabet characters before decoding.
+///
+/// By default whitespace is stripped, no separators are and look-alikes are replaced.
+#[derive(Debug, Clone)]
+pub struct LenientDecoder<'a> {
+	alphabet: &'a Alphabet,
+	whitespace: bool,
+	separators: &'a [char],
+	homoglyphs: bool,
+}
+
+impl<'a> Default for LenientDecoder<'a> {
+	fn default() -> Self {
+		LenientDecoder {
+			alphabet: &Alphabet::BITCOIN,
+			whitespace: true,
+			separators: &[],
+			homoglyphs: true,
+		}
+	}
+}
+
+impl<'a> LenientDecoder<'a> {
+	/// Creates a lenient decoder for the bitcoin alphabet with the default options.
+	pub fn new() -> Self {
+		LenientDecoder::default()
+	}
+
+	/// Sets the alphabet of the input.
+	pub fn alphabet(mut self, alphabet: &'a Alphabet) -> Self {
+		self.alphabet = alphabet;
+		self
+	}
+
+	/// Sets whether whitespace, including zero-width spaces and joiners, is stripped.
+	pub fn whitespace(mut self, strip: bool) -> Self {
+		self.whitespace = strip;
+		self
+	}
+
+	/// Sets additional separator characters to strip, e.g. `'-'` for addresses split into groups.
+	pub fn separators(mut self, separators: &'a [char]) -> Self {
+		self.separators = separators;
+		self
+	}
+
+	/// Sets whether Unicode look-alikes of alphabet characters, such as Cyrillic 'А' or full-width
+	/// digits, are replaced.
+	pub fn homoglyphs(mut self, replace: bool) -> Self {
+		self.homoglyphs = replace;
+		self
+	}
+
+	/// Normalizes and decodes `input`.
+	///
+	/// Characters which are neither in the alphabet nor normalized are reported with their position
+	/// in the original input.
+	pub fn decode(&self, input: &str) -> Result<Normalized, FromBase58Error> {
+		let mut normalized = String::with_capacity(input.len());
+		let mut normalizations = Vec::new();
+
+		for (char_index, (byte_offset, character)) in input.char_indices().enumerate() {
+			if self.alphabet.contains(character) {
+				normalized.push(character);
+			} else if (self.whitespace && is_whitespace(character)) || self.separators.contains(&character) {
+				normalizations.push(Normalization::Removed { character, byte_offset, char_index });
+			} else if let Some(to) = homoglyph(character).filter(|c| self.homoglyphs && self.alphabet.contains(*c)) {
+				normalized.push(to);
+				normalizations.push(Normalization::Replaced { from: character, to, byte_offset, char_index });
+			} else {
+				return Err(invalid_character(input, byte_offset));
+			}
+		}
+
+		Ok(Normalized {
+			bytes: decode(&normalized, self.alphabet)?,
+			normalizations,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{LenientDecoder, Normalization, Normalized};
+	use {Alphabet, FromBase58, FromBase58Error};
+
+	#[test]
+	fn test_lenient_valid_input() {
+		let decoded = LenientDecoder::new().decode("1ZiCa").unwrap();
+		assert_eq!(decoded, Normalized { bytes: b"\0abc".to_vec(), normalizations: vec![] });
+	}
+
+	#[test]
+	fn test_lenient_whitespace() {
+		let decoded = LenientDecoder::new().decode(" 1A1zP1eP5QGefi2\r\nDMPTfTL5SLm\u{200b}v7DivfNa\t").unwrap();
+		assert_eq!(decoded.bytes, "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".from_base58().unwrap());
+		assert_eq!(decoded.normalizations, [
+			Normalization::Removed { character: ' ', byte_offset: 0, char_index: 0 },
+			Normalization::Removed { character: '\r', byte_offset: 16, char_index: 16 },
+			Normalization::Removed { character: '\n', byte_offset: 17, char_index: 17 },
+			Normalization::Removed { character: '\u{200b}', byte_offset: 29, char_index: 29 },
+			Normalization::Removed { character: '\t', byte_offset: 40, char_index: 38 },
+		]);
+
+		assert_eq!(LenientDecoder::new().whitespace(false).decode("Zi Ca"),
+			Err(FromBase58Error::InvalidBase58Character { character: ' ', byte_offset: 2, char_index: 2 }));
+	}
+
+	#[test]
+	fn test_lenient_separators() {
+		let decoder = LenientDecoder::new().separators(&['-', '_']);
+		let decoded = decoder.decode("Zi-C_a").unwrap();
+		assert_eq!(decoded.bytes, b"abc");
+		assert_eq!(decoded.normalizations.len(), 2);
+		assert!(LenientDecoder::new().decode("Zi-Ca").is_err());
+	}
+
+	#[test]
+	fn test_lenient_homoglyphs() {
+		// Cyrillic 'С', full-width 'ａ'
+		let decoded = LenientDecoder::new().decode("1ZiСａ").unwrap();
+		assert_eq!(decoded.bytes, b"\0abc");
+		assert_eq!(decoded.normalizations, [
+			Normalization::Replaced { from: 'С', to: 'C', byte_offset: 3, char_index: 3 },
+			Normalization::Replaced { from: 'ａ', to: 'a', byte_offset: 5, char_index: 4 },
+		]);
+
+		assert_eq!(LenientDecoder::new().homoglyphs(false).decode("1ZiСa"),
+			Err(FromBase58Error::InvalidBase58Character { character: 'С', byte_offset: 3, char_index: 3 }));
+
+		// Cyrillic 'О' looks like 'O', which is not a base58 character either
+		assert_eq!(LenientDecoder::new().decode("Zi Оa"), Err(FromBase58Error::InvalidBase58Character {
+			character: 'О',
+			byte_offset: 3,
+			char_index: 3,
+		}));
+	}
+
+	#[test]
+	fn test_lenient_alphabet() {
+		let decoded = LenientDecoder::new().alphabet(&Alphabet::FLICKR).decode("1yH cz").unwrap();
+		assert_eq!(decoded.bytes, b"\0abc");
+	}
+}
@@ -2,173 +2,309 @@
 //!
 //! Based on https://github.com/trezor/trezor-crypto/blob/master/base58.c
 //! commit hash: c6e7d37
-//! works only up to 128 bytes
+//!
+//! The default `alloc` feature enables the `String` and `Vec` returning conversions. Without it
+//! the crate needs no allocator, see [`encode_into`] and [`decode_into`]. The `std` feature implements
+//! `std::error::Error` for the error types. The `hash160` feature adds the `hash160` function for deriving
+//! bitcoin addresses from public keys and scripts.
 #![no_std]
 
+#[cfg(feature = "alloc")]
 #[macro_use]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+mod address;
+mod alphabet;
+#[cfg(feature = "alloc")]
+mod check;
+#[cfg(feature = "alloc")]
+mod correct;
+mod decode;
+mod diagnostics;
+mod encode;
+mod keccak;
+#[cfg(feature = "alloc")]
+mod large;
+#[cfg(feature = "alloc")]
+mod lenient;
+mod monero;
+#[cfg(feature = "hash160")]
+mod ripemd160;
+#[cfg(feature = "alloc")]
+mod sha256;
+#[cfg(test)]
+mod test_util;
+mod validate;
 
+use core::fmt;
+use core::ops::Range;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
 use alloc::string::String;
 
-const ALPHABET: &'static [u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
-
-const B58_DIGITS_MAP: &'static [i8] = &[
-	-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,
-	-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,
-	-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,
-	-1, 0, 1, 2, 3, 4, 5, 6, 7, 8,-1,-1,-1,-1,-1,-1,
-	-1, 9,10,11,12,13,14,15,16,-1,17,18,19,20,21,-1,
-	22,23,24,25,26,27,28,29,30,31,32,-1,-1,-1,-1,-1,
-	-1,33,34,35,36,37,38,39,40,41,42,43,-1,44,45,46,
-	47,48,49,50,51,52,53,54,55,56,57,-1,-1,-1,-1,-1,
-];
+#[cfg(feature = "alloc")]
+pub use address::{BitcoinAddress, BitcoinAddressKind, BitcoinNetwork};
+#[cfg(feature = "hash160")]
+pub use address::{public_key_address, script_address};
+pub use alphabet::{Alphabet, AlphabetError};
+#[cfg(feature = "alloc")]
+pub use check::{ToBase58Check, FromBase58Check, Versioned, encode_versioned};
+#[cfg(feature = "alloc")]
+pub use correct::{correct_base58check, recover_case_base58check, Correction, Edit};
+pub use decode::{decode_into, decode_array};
+pub use diagnostics::{diagnose, Diagnostics, InvalidCharacter, InvalidKind};
+pub use encode::{encode_into, encode_array, max_encoded_len};
+#[cfg(feature = "alloc")]
+pub use lenient::{LenientDecoder, Normalization, Normalized};
+pub use monero::{encode_monero_into, decode_monero_into, monero_encoded_len};
+pub use monero::{MoneroAddress, MoneroAddressKind, MoneroNetwork};
+#[cfg(feature = "alloc")]
+pub use monero::{encode_monero, decode_monero};
+#[cfg(feature = "hash160")]
+pub use ripemd160::hash160;
+pub use validate::{validate, is_valid_base58, Info};
 
 /// Errors that can occur when decoding base58 encoded string.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum FromBase58Error {
 	/// The input contained a character which is not a part of the base58 format.
-	InvalidBase58Character(char, usize),
+	InvalidBase58Character {
+		/// The invalid character.
+		character: char,
+		/// Offset of the first byte of the character in the input.
+		byte_offset: usize,
+		/// Index of the character among the characters of the input.
+		char_index: usize,
+	},
 	/// The input had invalid length.
 	InvalidBase58Length,
+	/// The checksum computed from the decoded payload did not match the checksum stored in the input.
+	InvalidChecksum {
+		/// Checksum computed from the payload.
+		expected: [u8; 4],
+		/// Checksum found at the end of the input.
+		actual: [u8; 4],
+	},
+	/// The decoded data did not start with any of the expected version prefixes.
+	InvalidVersion {
+		/// The first bytes of the decoded data, without the checksum, followed by zeros if it is shorter.
+		found: [u8; 4],
+		/// Length of the decoded data in bytes, without the checksum.
+		len: usize,
+	},
+	/// The decoded data did not have the expected length.
+	UnexpectedLength {
+		/// Expected length in bytes.
+		expected: usize,
+		/// Actual length in bytes.
+		actual: usize,
+	},
+	/// The decoded data is longer than expected, by a length that is not known exactly.
+	///
+	/// Reported for inputs far longer than expected, whose bytes are not counted so that rejecting them
+	/// takes no work or memory proportional to the input.
+	TooLong {
+		/// Expected length in bytes.
+		expected: usize,
+		/// Lower bound of the actual length in bytes.
+		at_least: usize,
+	},
+	/// The output buffer is too small, a buffer of at least `required` bytes is large enough.
+	BufferTooSmall {
+		/// Size of the output buffer needed to decode the input.
+		required: usize,
+	},
+	/// The final block of a Monero block base58 input has a length no number of bytes encodes to.
+	InvalidBlockSize {
+		/// Offset of the block in the input.
+		byte_offset: usize,
+		/// Length of the block in characters.
+		length: usize,
+	},
+	/// A block of a Monero block base58 input decodes to a number which does not fit in its bytes.
+	BlockOverflow {
+		/// Offset of the block in the input.
+		byte_offset: usize,
+		/// Length of the block in characters.
+		length: usize,
+	},
+	/// A search over the candidates for a corrupted input would try more than the configured `limit`.
+	SearchSpaceTooLarge {
+		/// Number of characters the search varies, the letters of either case when recovering the case and every
+		/// character when correcting an edit.
+		ambiguous: usize,
+		/// Number of candidates the search would try, `usize::MAX` if it does not fit.
+		candidates: usize,
+		/// Maximum number of candidates to try.
+		limit: usize,
+	},
+}
+
+impl fmt::Display for FromBase58Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			FromBase58Error::InvalidBase58Character { character, char_index, .. } =>
+				write!(f, "invalid base58 character {:?} at position {}", character, char_index),
+			FromBase58Error::InvalidBase58Length => f.write_str("invalid base58 length"),
+			FromBase58Error::InvalidChecksum { expected, actual } => write!(
+				f,
+				"invalid checksum, expected {:08x}, found {:08x}",
+				u32::from_be_bytes(expected),
+				u32::from_be_bytes(actual)
+			),
+			FromBase58Error::InvalidVersion { found, len } => {
+				write!(f, "no expected version prefix in {} decoded bytes", len)?;
+				if len > 0 {
+					f.write_str(" starting ")?;
+				}
+				for byte in &found[..len.min(found.len())] {
+					write!(f, "{:02x}", byte)?;
+				}
+				Ok(())
+			},
+			FromBase58Error::UnexpectedLength { expected, actual } =>
+				write!(f, "expected {} bytes, got {}", expected, actual),
+			FromBase58Error::TooLong { expected, at_least } =>
+				write!(f, "expected {} bytes, got at least {}", expected, at_least),
+			FromBase58Error::BufferTooSmall { required } =>
+				write!(f, "output buffer too small, {} bytes required", required),
+			FromBase58Error::InvalidBlockSize { byte_offset, length } =>
+				write!(f, "invalid base58 block of {} characters at position {}", length, byte_offset),
+			FromBase58Error::BlockOverflow { byte_offset, .. } =>
+				write!(f, "base58 block at position {} does not fit in its bytes", byte_offset),
+			FromBase58Error::SearchSpaceTooLarge { ambiguous, candidates, limit } => write!(
+				f,
+				"search over {} ambiguous characters needs {} candidates, more than the limit of {}",
+				ambiguous,
+				candidates,
+				limit
+			),
+		}
+	}
+}
+
+impl FromBase58Error {
+	/// Returns the byte range of the input the error refers to, e.g. to highlight an invalid character.
+	pub fn span(&self) -> Option<Range<usize>> {
+		match *self {
+			FromBase58Error::InvalidBase58Character { character, byte_offset, .. } =>
+				Some(byte_offset..byte_offset + character.len_utf8()),
+			FromBase58Error::InvalidBlockSize { byte_offset, length } |
+			FromBase58Error::BlockOverflow { byte_offset, length } => Some(byte_offset..byte_offset + length),
+			_ => None,
+		}
+	}
+
+	/// Returns the error for decoded `data` without a known version prefix.
+	pub(crate) fn invalid_version(data: &[u8]) -> Self {
+		let mut found = [0u8; 4];
+		let len = data.len().min(found.len());
+		found[..len].copy_from_slice(&data[..len]);
+		FromBase58Error::InvalidVersion { found, len: data.len() }
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FromBase58Error {}
+
+/// Errors that can occur when encoding into a caller-provided buffer.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ToBase58Error {
+	/// The output buffer is too small, a buffer of at least `required` bytes is large enough.
+	BufferTooSmall {
+		/// Size of the output buffer needed to encode the input.
+		required: usize,
+	},
+}
+
+impl fmt::Display for ToBase58Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ToBase58Error::BufferTooSmall { required } =>
+				write!(f, "output buffer too small, {} bytes required", required),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ToBase58Error {}
+
+/// A trait for decoding base58 encoded values into fixed-size types.
+pub trait TryFromBase58: Sized {
+	/// Decodes `input`, failing unless it encodes exactly the bytes of `Self`.
+	fn try_from_base58(input: &str) -> Result<Self, FromBase58Error>;
+}
+
+impl<const N: usize> TryFromBase58 for [u8; N] {
+	fn try_from_base58(input: &str) -> Result<Self, FromBase58Error> {
+		decode_array(input)
+	}
 }
 
 /// A trait for converting a value to base58 encoded string.
+#[cfg(feature = "alloc")]
 pub trait ToBase58 {
 	/// Converts a value of `self` to a base58 value, returning the owned string.
 	fn to_base58(&self) -> String;
 }
 
 /// A trait for converting base58 encoded values.
+#[cfg(feature = "alloc")]
+#[allow(clippy::wrong_self_convention)]
 pub trait FromBase58 {
-	/// Convert a value of `self`, interpreted as base58 encoded data, into an owned vector of bytes, returning a vector.
+	/// Convert a value of `self`, interpreted as base58 encoded data, into an owned vector of bytes,
+	/// returning a vector.
 	fn from_base58(&self) -> Result<Vec<u8>, FromBase58Error>;
 }
 
+#[cfg(feature = "alloc")]
 impl ToBase58 for [u8] {
 	fn to_base58(&self) -> String {
-		let zcount = self.iter().take_while(|x| **x == 0).count();
-		let size = (self.len() - zcount) * 138 / 100 + 1;
-		let mut buffer = vec![0u8; size];
-
-		let mut i = zcount;
-		let mut high = size - 1;
-
-		while i < self.len() {
-			let mut carry = self[i] as u32;
-			let mut j = size - 1;
-
-			while j > high || carry != 0 {
-				carry += 256 * buffer[j] as u32;
-				buffer[j] = (carry % 58) as u8;
-				carry /= 58;
-
-				// in original trezor implementation it was underflowing
-				if j  > 0 {
-					j -= 1;
-				}
-			}
-
-			i += 1;
-			high = j;
-		}
-
-		let mut j = buffer.iter().take_while(|x| **x == 0).count();
-
-		let mut result = String::new();
-		for _ in 0..zcount {
-			result.push('1');
-		}
-
-		while j < size {
-			result.push(ALPHABET[buffer[j] as usize] as char);
-			j += 1;
-		}
-
-		result
+		encode::encode(self, &Alphabet::BITCOIN)
 	}
 }
 
+#[cfg(feature = "alloc")]
 impl FromBase58 for str {
 	fn from_base58(&self) -> Result<Vec<u8>, FromBase58Error> {
-		let mut bin = [0u8; 132];
-		let mut out = [0u32; (132 + 3) / 4];
-		let bytesleft = (bin.len() % 4) as u8;
-		let zeromask = match bytesleft {
-			0 => 0u32,
-			_ => 0xffffffff << (bytesleft * 8),
-		};
-
-		let zcount = self.chars().take_while(|x| *x == '1').count();
-		let mut i = zcount;
-		let b58: Vec<u8> = self.bytes().collect();
-
-		while i < self.len() {
-			if (b58[i] & 0x80) != 0 {
-				// High-bit set on invalid digit
-				return Err(FromBase58Error::InvalidBase58Character(b58[i] as char, i));
-			}
-
-			if B58_DIGITS_MAP[b58[i] as usize] == -1 {
-				// // Invalid base58 digit
-				return Err(FromBase58Error::InvalidBase58Character(b58[i] as char, i));
-			}
-
-			let mut c = B58_DIGITS_MAP[b58[i] as usize] as u64;
-			let mut j = out.len();
-			while j != 0 {
-				j -= 1;
-				let t = out[j] as u64 * 58 + c;
-				c = (t & 0x3f00000000) >> 32;
-				out[j] = (t & 0xffffffff) as u32;
-			}
-
-			if c != 0 {
-				// Output number too big (carry to the next int32)
-				return Err(FromBase58Error::InvalidBase58Length);
-			}
-
-			if (out[0] & zeromask) != 0 {
-				// Output number too big (last int32 filled too far)
-				return Err(FromBase58Error::InvalidBase58Length);
-			}
-
-			i += 1;
-		}
+		decode::decode(self, &Alphabet::BITCOIN)
+	}
+}
 
-		let mut i = 1;
-		let mut j = 0;
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+	use alloc::string::{String, ToString};
+	use alloc::vec::Vec;
+	use super::{ToBase58, FromBase58, FromBase58Error, ToBase58Error, Alphabet};
+	use test_util::xorshift;
+
+	/// Characters used to build every input of the exhaustive tests: all of ASCII
+	/// and a handful of multi-byte characters.
+	fn test_chars() -> Vec<char> {
+		let mut chars: Vec<char> = (0u8..0x80).map(char::from).collect();
+		chars.extend_from_slice(&['\u{80}', '\u{ff}', 'é', 'Ж', '€', '\u{fffd}', '😀']);
+		chars
+	}
 
-		bin[0] = match bytesleft {
-			3 => ((out[0] & 0xff0000) >> 16) as u8,
-			2 => ((out[0] & 0xff00) >> 8) as u8,
-			1 => {
-				j = 1;
-				(out[0] & 0xff) as u8
+	/// Decoding must never panic, and whatever decodes must encode back to the same string.
+	fn check_decode(input: &str) {
+		match input.from_base58() {
+			Ok(bytes) => assert_eq!(bytes.to_base58(), input),
+			Err(err @ FromBase58Error::InvalidBase58Character { .. }) => {
+				let span = err.span().unwrap();
+				assert!(input.is_char_boundary(span.start) && input.is_char_boundary(span.end));
+				// the first invalid character is reported
+				assert!(input[..span.start].from_base58().is_ok());
 			},
-			_ => {
-				i = 0;
-				bin[0]
-			}
-		};
-
-		while j < out.len() {
-			bin[i] = ((out[j] >> 0x18) & 0xff) as u8;
-			bin[i + 1] = ((out[j] >> 0x10) & 0xff) as u8;
-			bin[i + 2] = ((out[j] >> 8) & 0xff) as u8;
-			bin[i + 3] = ((out[j] >> 0) & 0xff) as u8;
-			i += 4;
-			j += 1;
+			Err(err) => panic!("unexpected error {:?} for {:?}", err, input),
 		}
-
-		let leading_zeros = bin.iter().take_while(|x| **x == 0).count();
-		Ok(bin[leading_zeros - zcount..].to_vec())
 	}
-}
-
-#[cfg(test)]
-mod tests {
-	use super::{ToBase58, FromBase58};
 
 	#[test]
 	fn test_from_base58_basic() {
@@ -207,6 +343,78 @@ mod tests {
 		assert_eq!("1111ZiCa".from_base58().unwrap(), b"\0\0\0\0abc");
 	}
 
+	#[test]
+	fn test_from_base58_long_input() {
+		let input: Vec<u8> = (0..1024).map(|x| (x * 7 + 3) as u8).collect();
+		assert_eq!(input.to_base58().from_base58().unwrap(), input);
+
+		let input = [0xffu8; 300];
+		assert_eq!(input.to_base58().from_base58().unwrap(), &input[..]);
+
+		let input = [0u8; 200];
+		assert_eq!(input.to_base58().from_base58().unwrap(), &input[..]);
+	}
+
+	#[test]
+	fn test_from_base58_all_short_inputs() {
+		let chars = test_chars();
+		let mut input = String::new();
+		check_decode(&input);
+		for a in &chars {
+			for b in &chars {
+				input.clear();
+				input.push(*a);
+				check_decode(&input);
+				input.push(*b);
+				check_decode(&input);
+			}
+		}
+
+		let chars = ['1', '2', 'z', '0', 'l', ' ', '\0', '\x7f', 'é', '😀'];
+		for a in &chars {
+			for b in &chars {
+				for c in &chars {
+					for d in &chars {
+						input.clear();
+						input.extend(&[*a, *b, *c, *d]);
+						check_decode(&input);
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_from_base58_leading_ones() {
+		for n in 0..600 {
+			let ones: String = (0..n).map(|_| '1').collect();
+			assert_eq!(ones.from_base58().unwrap(), vec![0u8; n]);
+
+			for tail in &["2", "z", "zzzzzzzzzz", "0", "é", "1é", "z!"] {
+				check_decode(&(ones.clone() + tail));
+			}
+		}
+	}
+
+	#[test]
+	fn test_from_base58_random_inputs() {
+		let chars = test_chars();
+		let mut state = 0x2545f491;
+		for len in 0..300 {
+			// random strings of valid characters, with a rare invalid one
+			let input: String = (0..len).map(|_| match xorshift(&mut state) % 64 {
+				0 => chars[xorshift(&mut state) as usize % chars.len()],
+				x => Alphabet::BITCOIN.as_str().as_bytes()[x as usize % 58] as char,
+			}).collect();
+			check_decode(&input);
+
+			// random bytes with a random amount of leading zeros must round trip
+			let zeros = xorshift(&mut state) as usize % 8;
+			let bytes: Vec<u8> = (0..len).map(|i| if i < zeros { 0 } else { xorshift(&mut state) as u8 }).collect();
+			assert_eq!(bytes.to_base58().from_base58().unwrap(), bytes);
+		}
+	}
+
 	#[test]
 	fn test_to_base58_basic() {
 		assert_eq!(b"".to_base58(), "");
@@ -229,4 +437,44 @@ mod tests {
 		assert_eq!(b"\0\0\0abc".to_base58(), "111ZiCa");
 		assert_eq!(b"\0\0\0\0abc".to_base58(), "1111ZiCa");
 	}
+
+	#[test]
+	fn test_from_base58_non_ascii() {
+		let err = "3mJé7".from_base58().unwrap_err();
+		assert_eq!(err, FromBase58Error::InvalidBase58Character { character: 'é', byte_offset: 3, char_index: 3 });
+		assert_eq!(err.span(), Some(3..5));
+
+		// Cyrillic 'А' and 'В' look like their Latin counterparts
+		let err = "3mJАВ".from_base58().unwrap_err();
+		assert_eq!(err, FromBase58Error::InvalidBase58Character { character: 'А', byte_offset: 3, char_index: 3 });
+		assert_eq!(err.span(), Some(3..5));
+
+		let err = "😀".from_base58().unwrap_err();
+		assert_eq!(err, FromBase58Error::InvalidBase58Character { character: '😀', byte_offset: 0, char_index: 0 });
+		assert_eq!(err.span(), Some(0..4));
+	}
+
+	#[test]
+	fn test_error_display() {
+		assert_eq!("3mJr0".from_base58().unwrap_err().to_string(), "invalid base58 character '0' at position 4");
+		assert_eq!("3mJé0".from_base58().unwrap_err().to_string(), "invalid base58 character 'é' at position 3");
+		assert_eq!(
+			FromBase58Error::InvalidChecksum { expected: [0xc2, 0x9b, 0x7d, 0x93], actual: [0, 0, 0, 1] }.to_string(),
+			"invalid checksum, expected c29b7d93, found 00000001"
+		);
+		assert_eq!(FromBase58Error::UnexpectedLength { expected: 32, actual: 33 }.to_string(),
+			"expected 32 bytes, got 33");
+		assert_eq!(FromBase58Error::InvalidVersion { found: [0x30, 0x12, 0, 0], len: 21 }.to_string(),
+			"no expected version prefix in 21 decoded bytes starting 30120000");
+		assert_eq!(FromBase58Error::InvalidVersion { found: [0x30, 0, 0, 0], len: 1 }.to_string(),
+			"no expected version prefix in 1 decoded bytes starting 30");
+		assert_eq!(FromBase58Error::InvalidVersion { found: [0; 4], len: 0 }.to_string(),
+			"no expected version prefix in 0 decoded bytes");
+		assert_eq!(FromBase58Error::TooLong { expected: 32, at_least: 40 }.to_string(),
+			"expected 32 bytes, got at least 40");
+		assert_eq!(ToBase58Error::BufferTooSmall { required: 45 }.to_string(),
+			"output buffer too small, 45 bytes required");
+		assert_eq!(FromBase58Error::SearchSpaceTooLarge { ambiguous: 34, candidates: 4069, limit: 4068 }.to_string(),
+			"search over 34 ambiguous characters needs 4069 candidates, more than the limit of 4068");
+	}
 }
@@ -0,0 +1,539 @@
+//! Monero block base58, 8-byte blocks encoded independently to 11 characters each, and Monero addresses
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::{self, FromStr};
+use decode::invalid_character;
+use keccak::keccak256;
+use {Alphabet, FromBase58Error, ToBase58Error};
+
+/// Number of bytes in a full block.
+const BLOCK_LEN: usize = 8;
+
+/// Number of characters a block of as many bytes as the index encodes to.
+const ENCODED_BLOCK_LEN: [usize; BLOCK_LEN + 1] = [0, 2, 3, 5, 6, 7, 9, 10, 11];
+
+/// Returns the length of the Monero block base58 encoding of `input_len` bytes.
+pub const fn monero_encoded_len(input_len: usize) -> usize {
+	(input_len / BLOCK_LEN) * ENCODED_BLOCK_LEN[BLOCK_LEN] + ENCODED_BLOCK_LEN[input_len % BLOCK_LEN]
+}
+
+/// Writes `input` in Monero block base58 into `output`, returning the number of bytes written.
+///
+/// Every 8 bytes are encoded to 11 characters, padded with leading '1's, and a final shorter block
+/// to as many characters as its length takes. No allocation is made, see [`monero_encoded_len`].
+pub fn encode_monero_into(input: &[u8], output: &mut [u8]) -> Result<usize, ToBase58Error> {
+	let required = monero_encoded_len(input.len());
+	if output.len() < required {
+		return Err(ToBase58Error::BufferTooSmall { required });
+	}
+
+	let alphabet = &Alphabet::BITCOIN;
+	let blocks = input.chunks(BLOCK_LEN);
+	let encoded_blocks = output[..required].chunks_mut(ENCODED_BLOCK_LEN[BLOCK_LEN]);
+	for (block, encoded) in blocks.zip(encoded_blocks) {
+		let mut number = block.iter().fold(0u64, |acc, x| acc << 8 | *x as u64);
+		for c in encoded.iter_mut().rev() {
+			*c = alphabet.char((number % 58) as u8);
+			number /= 58;
+		}
+	}
+
+	Ok(required)
+}
+
+/// Writes the bytes encoded by the Monero block base58 string `input` into `output`, returning the number
+/// of bytes written.
+///
+/// Fails with [`FromBase58Error::InvalidBlockSize`] if the final block has a length no number of bytes
+/// encodes to, and with [`FromBase58Error::BlockOverflow`] if a block does not fit in its bytes.
+pub fn decode_monero_into(input: &str, output: &mut [u8]) -> Result<usize, FromBase58Error> {
+	let alphabet = &Alphabet::BITCOIN;
+	let b58 = input.as_bytes();
+	if let Some(i) = b58.iter().position(|c| alphabet.digit(*c).is_none()) {
+		return Err(invalid_character(input, i));
+	}
+
+	let full_blocks = b58.len() / ENCODED_BLOCK_LEN[BLOCK_LEN];
+	let last_len = b58.len() % ENCODED_BLOCK_LEN[BLOCK_LEN];
+	let last_block = ENCODED_BLOCK_LEN.iter().position(|x| *x == last_len).ok_or(FromBase58Error::InvalidBlockSize {
+		byte_offset: b58.len() - last_len,
+		length: last_len,
+	})?;
+
+	let required = full_blocks * BLOCK_LEN + last_block;
+	if output.len() < required {
+		return Err(FromBase58Error::BufferTooSmall { required });
+	}
+
+	let encoded_blocks = b58.chunks(ENCODED_BLOCK_LEN[BLOCK_LEN]);
+	let blocks = output[..required].chunks_mut(BLOCK_LEN);
+	for (i, (encoded, block)) in encoded_blocks.zip(blocks).enumerate() {
+		// 58^11 does not fit in a u64
+		let number = encoded.iter().fold(0u128, |acc, c| acc * 58 + alphabet.digit(*c).unwrap_or(0) as u128);
+		if number >> (8 * block.len()) != 0 {
+			return Err(FromBase58Error::BlockOverflow {
+				byte_offset: i * ENCODED_BLOCK_LEN[BLOCK_LEN],
+				length: encoded.len(),
+			});
+		}
+
+		block.copy_from_slice(&number.to_be_bytes()[16 - block.len()..]);
+	}
+
+	Ok(required)
+}
+
+/// Converts `input` to a Monero block base58 string, see [`encode_monero_into`].
+#[cfg(feature = "alloc")]
+pub fn encode_monero(input: &[u8]) -> String {
+	let mut buffer = vec![0u8; monero_encoded_len(input.len())];
+	let len = encode_monero_into(input, &mut buffer).expect("buffer of monero_encoded_len is large enough");
+	buffer[..len].iter().map(|c| *c as char).collect()
+}
+
+/// Decodes a Monero block base58 string into an owned vector of bytes, see [`decode_monero_into`].
+#[cfg(feature = "alloc")]
+pub fn decode_monero(input: &str) -> Result<Vec<u8>, FromBase58Error> {
+	// a block never decodes to more bytes than it has characters
+	let mut output = vec![0u8; input.len()];
+	let len = decode_monero_into(input, &mut output)?;
+	output.truncate(len);
+	Ok(output)
+}
+
+/// Length of the public spend and view keys.
+const KEY_LEN: usize = 32;
+
+/// Length of the payment ID of an integrated address.
+const PAYMENT_ID_LEN: usize = 8;
+
+/// Number of Keccak-256 bytes appended to an address.
+const CHECKSUM_LEN: usize = 4;
+
+/// Longest decoded address, an integrated address. Every known prefix is below 128, a single varint byte.
+const MAX_ADDRESS_LEN: usize = 1 + 2 * KEY_LEN + PAYMENT_ID_LEN + CHECKSUM_LEN;
+
+/// Monero network an address belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoneroNetwork {
+	/// The main network.
+	Mainnet,
+	/// The test network.
+	Testnet,
+	/// The staging network.
+	Stagenet,
+}
+
+/// Kind of a Monero address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoneroAddressKind {
+	/// A standard address of the primary account.
+	Standard,
+	/// A standard address with a payment ID attached.
+	Integrated {
+		/// The 8-byte payment ID.
+		payment_id: [u8; PAYMENT_ID_LEN],
+	},
+	/// A subaddress, derived from the primary account's keys.
+	Subaddress,
+}
+
+/// A Monero address: network prefix, public spend and view keys and, for integrated addresses, a payment ID,
+/// followed by a Keccak-256 checksum in block base58.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MoneroAddress {
+	/// Network of the address.
+	pub network: MoneroNetwork,
+	/// Kind of the address.
+	pub kind: MoneroAddressKind,
+	/// Public spend key.
+	pub spend_key: [u8; KEY_LEN],
+	/// Public view key.
+	pub view_key: [u8; KEY_LEN],
+}
+
+impl MoneroNetwork {
+	/// Returns the prefixes of standard addresses, integrated addresses and subaddresses of the network,
+	/// as listed in Monero's `cryptonote_config.h`.
+	fn prefixes(self) -> [u64; 3] {
+		match self {
+			MoneroNetwork::Mainnet => [18, 19, 42],
+			MoneroNetwork::Testnet => [53, 54, 63],
+			MoneroNetwork::Stagenet => [24, 25, 36],
+		}
+	}
+}
+
+impl MoneroAddress {
+	/// Returns the network prefix of the address.
+	pub fn prefix(&self) -> u64 {
+		let [standard, integrated, subaddress] = self.network.prefixes();
+		match self.kind {
+			MoneroAddressKind::Standard => standard,
+			MoneroAddressKind::Integrated { .. } => integrated,
+			MoneroAddressKind::Subaddress => subaddress,
+		}
+	}
+
+	/// Returns the payment ID of an integrated address.
+	pub fn payment_id(&self) -> Option<[u8; PAYMENT_ID_LEN]> {
+		match self.kind {
+			MoneroAddressKind::Integrated { payment_id } => Some(payment_id),
+			_ => None,
+		}
+	}
+
+	/// Writes the address in block base58 into `output`, returning the number of bytes written.
+	///
+	/// No allocation is made, 106 bytes are enough for any address.
+	pub fn encode_into(&self, output: &mut [u8]) -> Result<usize, ToBase58Error> {
+		let mut data = [0u8; MAX_ADDRESS_LEN];
+		let mut len = write_varint(self.prefix(), &mut data);
+		let payment_id = self.payment_id();
+		let parts = [&self.spend_key[..], &self.view_key[..], payment_id.as_ref().map_or(&[][..], |x| &x[..])];
+		for part in parts.iter() {
+			data[len..len + part.len()].copy_from_slice(part);
+			len += part.len();
+		}
+
+		let checksum = keccak256(&data[..len]);
+		data[len..len + CHECKSUM_LEN].copy_from_slice(&checksum[..CHECKSUM_LEN]);
+		encode_monero_into(&data[..len + CHECKSUM_LEN], output)
+	}
+
+	/// Parses a Monero address, verifying its checksum and recognizing its network and kind by the prefix.
+	///
+	/// Inputs of the wrong length for their prefix fail with [`FromBase58Error::UnexpectedLength`] of the whole
+	/// decoded data, checksum included. Inputs longer than any address expect the longest possible address.
+	pub fn from_base58(input: &str) -> Result<Self, FromBase58Error> {
+		let mut data = [0u8; MAX_ADDRESS_LEN];
+		let len = match decode_monero_into(input, &mut data) {
+			Err(FromBase58Error::BufferTooSmall { required }) => Err(FromBase58Error::UnexpectedLength {
+				expected: MAX_ADDRESS_LEN,
+				actual: required,
+			}),
+			x => x,
+		}?;
+
+		let data = &data[..len];
+		let payload_len = len.checked_sub(CHECKSUM_LEN).ok_or(FromBase58Error::InvalidBase58Length)?;
+		let (payload, checksum) = data.split_at(payload_len);
+		let expected = keccak256(payload);
+		if expected[..CHECKSUM_LEN] != *checksum {
+			return Err(FromBase58Error::InvalidChecksum {
+				expected: [expected[0], expected[1], expected[2], expected[3]],
+				actual: [checksum[0], checksum[1], checksum[2], checksum[3]],
+			});
+		}
+
+		let (prefix, prefix_len) = read_varint(payload).ok_or_else(|| FromBase58Error::invalid_version(payload))?;
+		// the position of the prefix tells the kind: standard, integrated or subaddress
+		let networks = [MoneroNetwork::Mainnet, MoneroNetwork::Testnet, MoneroNetwork::Stagenet];
+		let (network, kind) = networks.iter()
+			.find_map(|network| network.prefixes().iter().position(|x| *x == prefix).map(|kind| (*network, kind)))
+			.ok_or_else(|| FromBase58Error::invalid_version(payload))?;
+
+		let keys = &payload[prefix_len..];
+		let keys_len = 2 * KEY_LEN + if kind == 1 { PAYMENT_ID_LEN } else { 0 };
+		if keys.len() != keys_len {
+			return Err(FromBase58Error::UnexpectedLength {
+				expected: prefix_len + keys_len + CHECKSUM_LEN,
+				actual: len,
+			});
+		}
+
+		let mut spend_key = [0u8; KEY_LEN];
+		let mut view_key = [0u8; KEY_LEN];
+		spend_key.copy_from_slice(&keys[..KEY_LEN]);
+		view_key.copy_from_slice(&keys[KEY_LEN..2 * KEY_LEN]);
+		let kind = match kind {
+			0 => MoneroAddressKind::Standard,
+			1 => {
+				let mut payment_id = [0u8; PAYMENT_ID_LEN];
+				payment_id.copy_from_slice(&keys[2 * KEY_LEN..]);
+				MoneroAddressKind::Integrated { payment_id }
+			},
+			_ => MoneroAddressKind::Subaddress,
+		};
+
+		Ok(MoneroAddress { network, kind, spend_key, view_key })
+	}
+}
+
+impl fmt::Display for MoneroAddress {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut buffer = [0u8; monero_encoded_len(MAX_ADDRESS_LEN)];
+		let len = self.encode_into(&mut buffer).map_err(|_| fmt::Error)?;
+		f.write_str(str::from_utf8(&buffer[..len]).map_err(|_| fmt::Error)?)
+	}
+}
+
+impl FromStr for MoneroAddress {
+	type Err = FromBase58Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		MoneroAddress::from_base58(s)
+	}
+}
+
+/// Writes `value` as a little-endian base 128 varint, returning the number of bytes written.
+fn write_varint(mut value: u64, output: &mut [u8]) -> usize {
+	let mut len = 0;
+	while value >= 0x80 {
+		output[len] = value as u8 | 0x80;
+		value >>= 7;
+		len += 1;
+	}
+	output[len] = value as u8;
+	len + 1
+}
+
+/// Reads a varint from the start of `input`, returning the value and the number of bytes read.
+///
+/// Like Monero's `read_varint`, only the shortest encoding of a value is accepted, so that every address has
+/// exactly one string: a zero final byte after the first one and bits past 64 are rejected.
+fn read_varint(input: &[u8]) -> Option<(u64, usize)> {
+	let mut value = 0u64;
+	for (i, byte) in input.iter().enumerate().take(10) {
+		if i == 9 && *byte > 1 {
+			return None;
+		}
+		value |= ((byte & 0x7f) as u64) << (7 * i);
+		if byte & 0x80 == 0 {
+			return if i > 0 && *byte == 0 { None } else { Some((value, i + 1)) };
+		}
+	}
+
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	#[cfg(feature = "alloc")]
+	use alloc::string::{String, ToString};
+	#[cfg(feature = "alloc")]
+	use alloc::vec::Vec;
+	#[cfg(feature = "alloc")]
+	use super::{encode_monero, decode_monero};
+	use super::{encode_monero_into, decode_monero_into, monero_encoded_len};
+	use super::{MoneroAddress, MoneroAddressKind, MoneroNetwork};
+	#[cfg(feature = "alloc")]
+	use keccak::keccak256;
+	use test_util::{unhex, unhex_into};
+	use {FromBase58Error, ToBase58Error};
+
+	const DONATION: &str =
+		"44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A";
+	const DONATION_SPEND: &str = "42f18fc61586554095b0799b5c4b6f00cdeb26a93b20540d366932c6001617b7";
+	const DONATION_VIEW: &str = "5db35109fbba7d5f275fef4b9c49e0cc1c84b219ec6ff652fda54f89f7f63c88";
+
+	// vectors from the Monero unit tests
+	const VECTORS: &[(&str, &str)] = &[
+		("", ""),
+		("00", "11"),
+		("39", "1z"),
+		("ff", "5Q"),
+		("0000", "111"),
+		("0039", "11z"),
+		("0100", "15R"),
+		("ffff", "LUv"),
+		("000000", "11111"),
+		("000039", "1111z"),
+		("010000", "11LUw"),
+		("ffffff", "2UzHL"),
+		("00000039", "11111z"),
+		("ffffffff", "7YXq9G"),
+		("0000000039", "111111z"),
+		("ffffffffff", "VtB5VXc"),
+		("ffffffffffff", "3CUsUpv9t"),
+		("ffffffffffffff", "Ahg1opVcGW"),
+		("ffffffffffffffff", "jpXCZedGfVQ"),
+		("0000000000000000", "11111111111"),
+		("06156013762879f7ffffffffff", "22222222222VtB5VXc"),
+		("ffffffffffffffffffffffffffffffffffff", "jpXCZedGfVQjpXCZedGfVQLUv"),
+	];
+
+	#[test]
+	fn test_encode_monero() {
+		let (mut buffer, mut output) = ([0u8; 32], [0u8; 32]);
+		for (hex, encoded) in VECTORS {
+			let input = unhex_into(hex, &mut buffer);
+			let written = encode_monero_into(input, &mut output).unwrap();
+			assert_eq!(&output[..written], encoded.as_bytes());
+			assert_eq!(monero_encoded_len(hex.len() / 2), encoded.len());
+			#[cfg(feature = "alloc")]
+			assert_eq!(encode_monero(input), *encoded);
+		}
+	}
+
+	#[test]
+	fn test_decode_monero() {
+		let (mut buffer, mut output) = ([0u8; 32], [0u8; 32]);
+		for (hex, encoded) in VECTORS {
+			let expected = unhex_into(hex, &mut buffer);
+			let written = decode_monero_into(encoded, &mut output).unwrap();
+			assert_eq!(&output[..written], expected);
+			#[cfg(feature = "alloc")]
+			assert_eq!(decode_monero(encoded).unwrap(), expected);
+		}
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_decode_monero_invalid_block_size() {
+		let inputs = [("1", 0), ("z", 0), ("1111", 0), ("zzzz", 0), ("11111111", 0), ("123456789AB1", 11)];
+		for (input, byte_offset) in inputs {
+			let length = input.len() - byte_offset;
+			assert_eq!(decode_monero(input), Err(FromBase58Error::InvalidBlockSize { byte_offset, length }));
+		}
+
+		let err = decode_monero("123456789AB1").unwrap_err();
+		assert_eq!(err.to_string(), "invalid base58 block of 1 characters at position 11");
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_decode_monero_overflow() {
+		for input in &["5R", "zz", "LUw", "zzz", "2UzHM", "7YXq9H", "jpXCZedGfVR", "zzzzzzzzzzz"] {
+			let length = input.len();
+			assert_eq!(decode_monero(input), Err(FromBase58Error::BlockOverflow { byte_offset: 0, length }));
+		}
+
+		let err = decode_monero("11111111111jpXCZedGfVR").unwrap_err();
+		assert_eq!(err, FromBase58Error::BlockOverflow { byte_offset: 11, length: 11 });
+		assert_eq!(err.span(), Some(11..22));
+	}
+
+	#[test]
+	fn test_monero_small_buffer() {
+		let mut output = [0u8; 10];
+		assert_eq!(encode_monero_into(&[0xff; 8], &mut output), Err(ToBase58Error::BufferTooSmall { required: 11 }));
+		assert_eq!(decode_monero_into("jpXCZedGfVQLUv", &mut output[..9]),
+			Err(FromBase58Error::BufferTooSmall { required: 10 }));
+		assert_eq!(decode_monero_into("jpXCZedGfVQLUv", &mut output), Ok(10));
+		assert_eq!(decode_monero_into("jpXCZedGf0QLUv", &mut output), Err(FromBase58Error::InvalidBase58Character {
+			character: '0',
+			byte_offset: 9,
+			char_index: 9,
+		}));
+	}
+
+	#[test]
+	fn test_parse_monero_address() {
+		let address: MoneroAddress = DONATION.parse().unwrap();
+		assert_eq!(address, MoneroAddress {
+			network: MoneroNetwork::Mainnet,
+			kind: MoneroAddressKind::Standard,
+			spend_key: unhex(DONATION_SPEND),
+			view_key: unhex(DONATION_VIEW),
+		});
+		assert_eq!(address.prefix(), 18);
+
+		let integrated = MoneroAddress::from_base58(
+			"4LL9oSLmtpccfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2bYXZKKQePHES9khPK"
+		).unwrap();
+		assert_eq!(integrated.network, MoneroNetwork::Mainnet);
+		assert_eq!(integrated.payment_id(), Some([0x8a, 0x12, 0x50, 0x52, 0xfe, 0x6f, 0x38, 0x77]));
+		assert_eq!(integrated.spend_key, unhex("eda9fe8dfcdd25d5430ea64229d04f6b41b2e5a1587c29cd499a63eb79d11711"));
+		assert_eq!(integrated.view_key, unhex("3076a02b73d130fb904c9e91075fcd16f735c6850dfadb125eb826d96a113f09"));
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_parse_monero_address_networks() {
+		let addresses = [
+			("888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H",
+				MoneroNetwork::Mainnet, MoneroAddressKind::Subaddress),
+			("9wviCeWe2D8XS82k2ovp5EUYLzBt9pYNW2LXUFsZiv8S3Mt21FZ5qQaAroko1enzw3eGr9qC7X1D7Geoo2RrAotYPwq9Gm8",
+				MoneroNetwork::Testnet, MoneroAddressKind::Standard),
+			("55LTR8KniP4LQGJSPtbYDacR7dz8RBFnsfAKMaMuwUNYX6aQbBcovzDPyrQF9KXF9tVU6Xk3K8no1BywnJX6GvZX8yJsXvt",
+				MoneroNetwork::Stagenet, MoneroAddressKind::Standard),
+			("73a4nWuvkYoYoksGurDjKZQcZkmaxLaKbbeiKzHnMmqKivrCzq5Q2JtJG1UZNZFqLPbQ3MiXCk2Q5bdwdUNSr7X9QrPubkn",
+				MoneroNetwork::Stagenet, MoneroAddressKind::Subaddress),
+		];
+
+		for (input, network, kind) in addresses {
+			let address = MoneroAddress::from_base58(input).unwrap();
+			assert_eq!((address.network, address.kind), (network, kind));
+			assert_eq!(address.to_string(), input);
+		}
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_construct_monero_address() {
+		let mut address = MoneroAddress {
+			network: MoneroNetwork::Mainnet,
+			kind: MoneroAddressKind::Standard,
+			spend_key: unhex("eda9fe8dfcdd25d5430ea64229d04f6b41b2e5a1587c29cd499a63eb79d11711"),
+			view_key: unhex("3076a02b73d130fb904c9e91075fcd16f735c6850dfadb125eb826d96a113f09"),
+		};
+		assert_eq!(address.to_string(),
+			"4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge");
+
+		address.kind = MoneroAddressKind::Integrated { payment_id: [0x8a, 0x12, 0x50, 0x52, 0xfe, 0x6f, 0x38, 0x77] };
+		assert_eq!(address.to_string(), concat!(
+			"4LL9oSLmtpccfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2",
+			"bYXZKKQePHES9khPK"
+		));
+
+		let mut output = [0u8; 106];
+		assert_eq!(address.encode_into(&mut output), Ok(106));
+		assert_eq!(address.encode_into(&mut output[..105]), Err(ToBase58Error::BufferTooSmall { required: 106 }));
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_parse_monero_address_invalid() {
+		// last character changed
+		let mut altered = String::from(DONATION);
+		altered.pop();
+		altered.push('B');
+		assert!(matches!(MoneroAddress::from_base58(&altered), Err(FromBase58Error::InvalidChecksum { .. })));
+
+		assert_eq!(MoneroAddress::from_base58(""), Err(FromBase58Error::InvalidBase58Length));
+		assert_eq!(MoneroAddress::from_base58(&DONATION[..92]), Err(FromBase58Error::InvalidBlockSize {
+			byte_offset: 88,
+			length: 4,
+		}));
+
+		let with_checksum = |data: &[u8]| {
+			let mut data = data.to_vec();
+			data.extend_from_slice(&keccak256(&data)[..4]);
+			encode_monero(&data)
+		};
+
+		// a bitcoin style version byte
+		let mut data = decode_monero(DONATION).unwrap();
+		data.truncate(65);
+		data[0] = 0;
+		assert_eq!(MoneroAddress::from_base58(&with_checksum(&data)),
+			Err(FromBase58Error::InvalidVersion { found: [0x00, 0x42, 0xf1, 0x8f], len: 65 }));
+		// a varint which never ends
+		assert_eq!(MoneroAddress::from_base58(&with_checksum(&[0xff; 12])),
+			Err(FromBase58Error::InvalidVersion { found: [0xff; 4], len: 12 }));
+		// a varint with bits past 64
+		let mut overflow = [0xffu8; 10];
+		overflow[9] = 0x02;
+		assert_eq!(MoneroAddress::from_base58(&with_checksum(&overflow)),
+			Err(FromBase58Error::InvalidVersion { found: [0xff; 4], len: 10 }));
+		// 18 in two bytes, a longer encoding of the mainnet prefix than the wallet writes
+		let mut padded = Vec::from([0x92, 0x00]);
+		padded.extend_from_slice(&data[1..]);
+		assert_eq!(MoneroAddress::from_base58(&with_checksum(&padded)),
+			Err(FromBase58Error::InvalidVersion { found: [0x92, 0x00, 0x42, 0xf1], len: 66 }));
+		// integrated address prefix without a payment ID
+		data[0] = 19;
+		assert_eq!(MoneroAddress::from_base58(&with_checksum(&data)),
+			Err(FromBase58Error::UnexpectedLength { expected: 77, actual: 69 }));
+		// longer than any address
+		assert_eq!(MoneroAddress::from_base58(&encode_monero(&[0u8; 100])),
+			Err(FromBase58Error::UnexpectedLength { expected: 77, actual: 100 }));
+		assert_eq!(MoneroAddress::from_base58(&encode_monero(&[0u8; 78])),
+			Err(FromBase58Error::UnexpectedLength { expected: 77, actual: 78 }));
+	}
+}
@@ -0,0 +1,148 @@
+//! Minimal RIPEMD-160 implementation, used for HASH160 of public keys and scripts
+
+use sha256::sha256;
+
+const INITIAL_STATE: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// Message words selected by every step of the left line.
+const LEFT_WORDS: [usize; 80] = [
+	0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+	7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8,
+	3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12,
+	1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2,
+	4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+
+/// Message words selected by every step of the right line.
+const RIGHT_WORDS: [usize; 80] = [
+	5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12,
+	6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2,
+	15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13,
+	8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14,
+	12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+/// Rotation of every step of the left line.
+const LEFT_ROTATIONS: [u32; 80] = [
+	11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8,
+	7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12,
+	11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5,
+	11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12,
+	9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+
+/// Rotation of every step of the right line.
+const RIGHT_ROTATIONS: [u32; 80] = [
+	8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6,
+	9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11,
+	9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5,
+	15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8,
+	8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+const LEFT_CONSTANTS: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+const RIGHT_CONSTANTS: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// Boolean function of the `round`th round of the left line, the right line uses them in reverse order.
+fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+	match round {
+		0 => x ^ y ^ z,
+		1 => (x & y) | (!x & z),
+		2 => (x | !y) ^ z,
+		3 => (x & z) | (y & !z),
+		_ => x ^ (y | !z),
+	}
+}
+
+fn compress(state: &mut [u32; 5], block: &[u8]) {
+	let mut x = [0u32; 16];
+	for (word, chunk) in x.iter_mut().zip(block.chunks(4)) {
+		*word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+	}
+
+	let mut left = *state;
+	let mut right = *state;
+	for i in 0..80 {
+		let round = i / 16;
+		let [a, b, c, d, e] = left;
+		let t = a.wrapping_add(f(round, b, c, d))
+			.wrapping_add(x[LEFT_WORDS[i]])
+			.wrapping_add(LEFT_CONSTANTS[round])
+			.rotate_left(LEFT_ROTATIONS[i])
+			.wrapping_add(e);
+		left = [e, t, b, c.rotate_left(10), d];
+
+		let [a, b, c, d, e] = right;
+		let t = a.wrapping_add(f(4 - round, b, c, d))
+			.wrapping_add(x[RIGHT_WORDS[i]])
+			.wrapping_add(RIGHT_CONSTANTS[round])
+			.rotate_left(RIGHT_ROTATIONS[i])
+			.wrapping_add(e);
+		right = [e, t, b, c.rotate_left(10), d];
+	}
+
+	let t = state[1].wrapping_add(left[2]).wrapping_add(right[3]);
+	state[1] = state[2].wrapping_add(left[3]).wrapping_add(right[4]);
+	state[2] = state[3].wrapping_add(left[4]).wrapping_add(right[0]);
+	state[3] = state[4].wrapping_add(left[0]).wrapping_add(right[1]);
+	state[4] = state[0].wrapping_add(left[1]).wrapping_add(right[2]);
+	state[0] = t;
+}
+
+/// Computes the RIPEMD-160 digest of `data`.
+pub fn ripemd160(data: &[u8]) -> [u8; 20] {
+	let mut state = INITIAL_STATE;
+	let mut blocks = data.chunks_exact(64);
+	for block in blocks.by_ref() {
+		compress(&mut state, block);
+	}
+
+	let rest = blocks.remainder();
+	let mut last = [0u8; 128];
+	last[..rest.len()].copy_from_slice(rest);
+	last[rest.len()] = 0x80;
+	let len = if rest.len() < 56 { 64 } else { 128 };
+	last[len - 8..len].copy_from_slice(&(data.len() as u64).wrapping_mul(8).to_le_bytes());
+	for block in last[..len].chunks(64) {
+		compress(&mut state, block);
+	}
+
+	let mut result = [0u8; 20];
+	for (chunk, word) in result.chunks_mut(4).zip(state.iter()) {
+		chunk.copy_from_slice(&word.to_le_bytes());
+	}
+	result
+}
+
+/// Computes RIPEMD-160 of SHA-256, as used by bitcoin to hash public keys and scripts.
+///
+/// [`encode_versioned`](::encode_versioned) turns the hash into an address with any version byte, e.g. of
+/// other coins.
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+	ripemd160(&sha256(data))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ripemd160, hash160};
+	use test_util::unhex;
+
+	#[test]
+	fn test_ripemd160() {
+		assert_eq!(ripemd160(b""), unhex("9c1185a5c5e9fc54612808977ee8f548b2258d31"));
+		assert_eq!(ripemd160(b"abc"), unhex("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"));
+		assert_eq!(ripemd160(b"message digest"), unhex("5d0689ef49d2fae572b881b123a85ffa21595f36"));
+		assert_eq!(
+			ripemd160(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+			unhex("12a053384a9c0c88e405a06c27dcf49ada62eb2b")
+		);
+
+		let million = vec![b'a'; 1_000_000];
+		assert_eq!(ripemd160(&million), unhex("52783243c1697bdbe16d37f97f68f08325dc1528"));
+	}
+
+	#[test]
+	fn test_hash160() {
+		assert_eq!(hash160(b"hello"), unhex("b6a9c8c230722b7c748331a8b450f05566dc7d0f"));
+	}
+}
@@ -0,0 +1,146 @@
+//! Minimal SHA-256 implementation, used for base58check checksums
+
+const K: [u32; 64] = [
+	0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+	0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+	0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+	0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+	0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+	0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+	0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+	0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const INITIAL_STATE: [u32; 8] = [
+	0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Incremental SHA-256 hasher.
+#[derive(Clone)]
+pub struct Sha256 {
+	state: [u32; 8],
+	block: [u8; 64],
+	block_len: usize,
+	total_len: u64,
+}
+
+impl Sha256 {
+	pub fn new() -> Sha256 {
+		Sha256 {
+			state: INITIAL_STATE,
+			block: [0u8; 64],
+			block_len: 0,
+			total_len: 0,
+		}
+	}
+
+	pub fn update(&mut self, mut data: &[u8]) {
+		self.total_len = self.total_len.wrapping_add(data.len() as u64);
+		while !data.is_empty() {
+			let take = (64 - self.block_len).min(data.len());
+			self.block[self.block_len..self.block_len + take].copy_from_slice(&data[..take]);
+			self.block_len += take;
+			data = &data[take..];
+
+			if self.block_len == 64 {
+				compress(&mut self.state, &self.block);
+				self.block_len = 0;
+			}
+		}
+	}
+
+	pub fn finish(mut self) -> [u8; 32] {
+		let bit_len = self.total_len.wrapping_mul(8);
+		self.block[self.block_len] = 0x80;
+		self.block[self.block_len + 1..].iter_mut().for_each(|x| *x = 0);
+		if self.block_len >= 56 {
+			compress(&mut self.state, &self.block);
+			self.block = [0u8; 64];
+		}
+		self.block[56..].copy_from_slice(&bit_len.to_be_bytes());
+		compress(&mut self.state, &self.block);
+
+		let mut result = [0u8; 32];
+		for (chunk, word) in result.chunks_mut(4).zip(self.state.iter()) {
+			chunk.copy_from_slice(&word.to_be_bytes());
+		}
+		result
+	}
+}
+
+fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+	let mut w = [0u32; 64];
+	for (word, chunk) in w.iter_mut().zip(block.chunks(4)) {
+		*word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+	}
+
+	for i in 16..64 {
+		let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+		let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+		w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+	}
+
+	let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+	for i in 0..64 {
+		let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+		let ch = (e & f) ^ (!e & g);
+		let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+		let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+		let maj = (a & b) ^ (a & c) ^ (b & c);
+		let t2 = s0.wrapping_add(maj);
+
+		h = g;
+		g = f;
+		f = e;
+		e = d.wrapping_add(t1);
+		d = c;
+		c = b;
+		b = a;
+		a = t1.wrapping_add(t2);
+	}
+
+	for (word, value) in state.iter_mut().zip(&[a, b, c, d, e, f, g, h]) {
+		*word = word.wrapping_add(*value);
+	}
+}
+
+/// Computes the SHA-256 digest of `data`.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+	let mut hasher = Sha256::new();
+	hasher.update(data);
+	hasher.finish()
+}
+
+/// Computes SHA-256 applied twice, as used by bitcoin.
+pub fn double_sha256(data: &[u8]) -> [u8; 32] {
+	sha256(&sha256(data))
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+	use super::{sha256, double_sha256, Sha256};
+	use test_util::unhex;
+
+	#[test]
+	fn test_sha256() {
+		assert_eq!(sha256(b""), unhex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"));
+		assert_eq!(sha256(b"abc"), unhex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+		assert_eq!(
+			sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+			unhex("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1")
+		);
+		assert_eq!(double_sha256(b"hello"), unhex("9595c9df90075148eb06860365df33584b75bff782a510c6cd4883a419833d50"));
+	}
+
+	#[test]
+	fn test_sha256_incremental() {
+		let data: Vec<u8> = (0..1000u32).map(|x| x as u8).collect();
+		for split in &[0, 1, 55, 56, 63, 64, 65, 500, 1000] {
+			let mut hasher = Sha256::new();
+			hasher.update(&data[..*split]);
+			hasher.update(&data[*split..]);
+			assert_eq!(hasher.finish(), sha256(&data));
+		}
+	}
+}
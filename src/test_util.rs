@@ -0,0 +1,33 @@
+//! Helpers shared by the unit tests
+
+/// Decodes the hex string `hex` into the front of `output`, returning the decoded bytes.
+pub fn unhex_into<'a>(hex: &str, output: &'a mut [u8]) -> &'a [u8] {
+	let output = &mut output[..hex.len() / 2];
+	for (byte, pair) in output.iter_mut().zip(hex.as_bytes().chunks(2)) {
+		*byte = u8::from_str_radix(core::str::from_utf8(pair).unwrap(), 16).unwrap();
+	}
+	output
+}
+
+/// Decodes the hex string `hex` of exactly `N` bytes, e.g. a digest or a key.
+pub fn unhex<const N: usize>(hex: &str) -> [u8; N] {
+	assert_eq!(hex.len(), 2 * N, "{}", hex);
+	let mut output = [0u8; N];
+	unhex_into(hex, &mut output);
+	output
+}
+
+/// Xorshift32, reproducible pseudo random test inputs.
+#[cfg(feature = "alloc")]
+pub fn xorshift(state: &mut u32) -> u32 {
+	*state ^= *state << 13;
+	*state ^= *state >> 17;
+	*state ^= *state << 5;
+	*state
+}
+
+/// Returns `N` bytes starting with `zeros` zero bytes, followed by a pattern varying with `seed`.
+#[cfg(feature = "alloc")]
+pub fn with_zeros<const N: usize>(zeros: usize, seed: usize) -> [u8; N] {
+	core::array::from_fn(|i| if i < zeros { 0 } else { (i * 89 + seed * 7 + 3) as u8 })
+}
@@ -0,0 +1,90 @@
+//! Validation of base58 strings without decoding them
+
+use decode::invalid_character;
+use {Alphabet, FromBase58Error};
+
+/// What can be told about a valid base58 string without decoding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Info {
+	/// Number of leading zero digits, each of which decodes to a zero byte.
+	pub leading_zeros: usize,
+	/// Lower bound of the decoded length in bytes.
+	pub min_decoded_len: usize,
+	/// Upper bound of the decoded length in bytes.
+	pub max_decoded_len: usize,
+}
+
+/// Returns whether `input` consists of base58 characters only.
+pub fn is_valid_base58(input: &str) -> bool {
+	validate(input).is_ok()
+}
+
+/// Checks that `input` consists of base58 characters only, without allocating or decoding it.
+pub fn validate(input: &str) -> Result<Info, FromBase58Error> {
+	validate_with(input, &Alphabet::BITCOIN)
+}
+
+pub(crate) fn validate_with(input: &str, alphabet: &Alphabet) -> Result<Info, FromBase58Error> {
+	let b58 = input.as_bytes();
+	if let Some(i) = b58.iter().position(|c| alphabet.digit(*c).is_none()) {
+		return Err(invalid_character(input, i));
+	}
+
+	let leading_zeros = b58.iter().take_while(|x| **x == alphabet.char(0)).count();
+	let digits = (b58.len() - leading_zeros) as u128;
+	let (min, max) = match digits {
+		0 => (0, 0),
+		// `digits` base58 digits, the first of which is not zero, encode a number in
+		// [58^(digits - 1), 58^digits), which takes floor(log256(number)) + 1 bytes;
+		// log256(58) = 0.7322462..., rounded down for the lower bound and up for the upper one
+		_ => ((digits - 1) * 732_246 / 1_000_000 + 1, digits * 732_247 / 1_000_000 + 1),
+	};
+
+	Ok(Info {
+		leading_zeros,
+		min_decoded_len: leading_zeros + min as usize,
+		max_decoded_len: leading_zeros + max as usize,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{validate, is_valid_base58, Info};
+	#[cfg(feature = "alloc")]
+	use {ToBase58, FromBase58};
+	use FromBase58Error;
+
+	#[test]
+	fn test_validate() {
+		assert_eq!(validate(""), Ok(Info { leading_zeros: 0, min_decoded_len: 0, max_decoded_len: 0 }));
+		assert_eq!(validate("111"), Ok(Info { leading_zeros: 3, min_decoded_len: 3, max_decoded_len: 3 }));
+		assert_eq!(validate("1ZiCa"), Ok(Info { leading_zeros: 1, min_decoded_len: 4, max_decoded_len: 4 }));
+		assert_eq!(validate("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"),
+			Ok(Info { leading_zeros: 1, min_decoded_len: 25, max_decoded_len: 26 }));
+		assert_eq!(validate("ZiC0"), Err(FromBase58Error::InvalidBase58Character {
+			character: '0',
+			byte_offset: 3,
+			char_index: 3,
+		}));
+
+		assert!(is_valid_base58("3mJr7AoUXx2Wqd"));
+		assert!(!is_valid_base58("3mJr7AoUXx2Wqd "));
+		assert!(!is_valid_base58("3mJé"));
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_validate_bounds() {
+		for len in 0..300 {
+			for fill in &[0x01, 0x80, 0xff] {
+				let input: [u8; 300] = core::array::from_fn(|i| if i < len / 3 { 0 } else { *fill });
+				let encoded = input[..len].to_base58();
+				let info = validate(&encoded).unwrap();
+				let decoded = encoded.from_base58().unwrap().len();
+				assert_eq!(info.leading_zeros, len / 3);
+				assert!(info.min_decoded_len <= decoded && decoded <= info.max_decoded_len, "{:?} {}", info, decoded);
+				assert!(info.max_decoded_len - info.min_decoded_len <= 1);
+			}
+		}
+	}
+}